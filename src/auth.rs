@@ -1,12 +1,52 @@
+use crate::session::{forget_session, load_session};
 use anyhow::{Context, Result};
-use std::io::ErrorKind;
+use std::io::{ErrorKind, IsTerminal};
+use std::path::Path;
 use std::process::{Command, Stdio};
 
-pub fn unlock_vault() -> Result<()> {
+/// Where to read the master password from when unlocking without a prompt
+pub enum PasswordSource {
+    /// Name of an environment variable holding the password (`bw unlock --passwordenv`)
+    Env(String),
+    /// File whose first line is the password (`bw unlock --passwordfile`)
+    File(String),
+}
+
+impl PasswordSource {
+    fn check(&self) -> Result<()> {
+        match self {
+            PasswordSource::Env(var) if std::env::var_os(var).is_none() => {
+                anyhow::bail!("--passwordenv: environment variable '{}' is not set", var)
+            }
+            PasswordSource::File(path) if !Path::new(path).is_file() => {
+                anyhow::bail!("--passwordfile: '{}' is not a readable file", path)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn args(&self) -> [&str; 2] {
+        match self {
+            PasswordSource::Env(var) => ["--passwordenv", var],
+            PasswordSource::File(path) => ["--passwordfile", path],
+        }
+    }
+}
+
+pub fn unlock_vault(password: Option<&PasswordSource>) -> Result<()> {
     ensure_bw_cli_available()?;
     ensure_logged_in()?;
 
-    println!("Unlocking Bitwarden vault...");
+    // Reuse the session cached by `bw-env unlock`, unless one was exported already
+    let cached = std::env::var_os("BW_SESSION")
+        .is_none()
+        .then(load_session)
+        .flatten();
+    if let Some(session) = &cached {
+        std::env::set_var("BW_SESSION", session);
+    }
+
+    eprintln!("Unlocking Bitwarden vault...");
     let status = Command::new("bw")
         .arg("unlock")
         .arg("--check")
@@ -17,11 +57,29 @@ pub fn unlock_vault() -> Result<()> {
         .context("Failed to check Bitwarden vault status")?;
     
     if !status.success() {
+        if cached.is_some() {
+            // The cached session was invalidated, e.g. by `bw lock` or a new login
+            let _ = forget_session();
+            std::env::remove_var("BW_SESSION");
+        }
+
         // Vault is locked, need to unlock
-        let unlock_output = Command::new("bw")
-            .arg("unlock")
-            .arg("--raw")
-            .stdin(std::process::Stdio::inherit())
+        let mut unlock = Command::new("bw");
+        unlock.arg("unlock").arg("--raw");
+        match password {
+            Some(password) => {
+                // Only checked here, so CI can pass the flag whether or not the vault is locked
+                password.check()?;
+                unlock.args(password.args()).stdin(Stdio::null());
+            }
+            None if !std::io::stdin().is_terminal() => anyhow::bail!(
+                "The Bitwarden vault is locked and there is no terminal to prompt for the master password. Pass --passwordenv VAR or --passwordfile PATH, or run 'bw-env unlock' first."
+            ),
+            None => {
+                unlock.stdin(Stdio::inherit());
+            }
+        }
+        let unlock_output = unlock
             .stderr(std::process::Stdio::inherit())
             .output()
             .context("Failed to unlock Bitwarden vault")?;
@@ -35,15 +93,16 @@ pub fn unlock_vault() -> Result<()> {
         
         // Set the session key as environment variable
         std::env::set_var("BW_SESSION", session_key.trim());
-        println!("Vault unlocked successfully.");
+        eprintln!("Vault unlocked successfully.");
     } else {
-        println!("Vault is already unlocked.");
+        eprintln!("Vault is already unlocked.");
     }
     
     Ok(())
 }
 
-/// Ensure the user is logged into Bitwarden; if not, start the interactive login flow.
+/// Ensure the user is logged into Bitwarden; if not, log in with the API key from
+/// `BW_CLIENTID`/`BW_CLIENTSECRET` when set, or else start the interactive login flow.
 pub fn ensure_logged_in() -> Result<()> {
     let login_check = Command::new("bw")
         .arg("login")
@@ -57,17 +116,44 @@ pub fn ensure_logged_in() -> Result<()> {
         return Ok(());
     }
 
-    println!("No active Bitwarden login found. Starting 'bw login'...");
+    let has_api_key = ["BW_CLIENTID", "BW_CLIENTSECRET"]
+        .iter()
+        .all(|var| std::env::var_os(var).is_some_and(|value| !value.is_empty()));
+    if has_api_key {
+        eprintln!("No active Bitwarden login found. Logging in with the API key from BW_CLIENTID...");
+        // bw reads the client id and secret from the environment itself
+        let login_status = Command::new("bw")
+            .args(["login", "--apikey"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::from(std::io::stderr()))
+            .stderr(Stdio::inherit())
+            .status()
+            .context("Failed to run Bitwarden login")?;
+        if !login_status.success() {
+            anyhow::bail!("Bitwarden API key login failed. Check BW_CLIENTID and BW_CLIENTSECRET.");
+        }
+        eprintln!("Login completed successfully.");
+        return Ok(());
+    }
+
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "Not logged in to Bitwarden and there is no terminal for an interactive login. Set BW_CLIENTID and BW_CLIENTSECRET to log in with an API key."
+        );
+    }
+
+    eprintln!("No active Bitwarden login found. Starting 'bw login'...");
+    // bw prints its prompts to stdout; send them to stderr so stdout stays clean for output
     let login_status = Command::new("bw")
         .arg("login")
         .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
+        .stdout(Stdio::from(std::io::stderr()))
         .stderr(Stdio::inherit())
         .status()
         .context("Failed to run Bitwarden login")?;
 
     if login_status.success() {
-        println!("Login completed successfully.");
+        eprintln!("Login completed successfully.");
         Ok(())
     } else {
         anyhow::bail!("Bitwarden login failed. Please re-run and complete authentication.");
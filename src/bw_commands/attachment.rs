@@ -0,0 +1,53 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Upload a file as an attachment of the given item
+pub fn create_attachment(item_id: &str, file_path: &str) -> Result<()> {
+    eprintln!("Uploading attachment '{}'...", file_path);
+
+    let create_output = Command::new("bw")
+        .args(["create", "attachment", "--file", file_path, "--itemid", item_id])
+        .output()
+        .context("Failed to create Bitwarden attachment")?;
+
+    if !create_output.status.success() {
+        anyhow::bail!(
+            "Bitwarden CLI failed to upload attachment (attachments require a premium account): {}",
+            String::from_utf8_lossy(&create_output.stderr).trim()
+        );
+    }
+
+    eprintln!("Uploaded attachment '{}' successfully.", file_path);
+    Ok(())
+}
+
+/// Download the raw bytes of an item's attachment
+pub fn get_attachment(item_id: &str, attachment_id: &str) -> Result<Vec<u8>> {
+    let get_output = Command::new("bw")
+        .args(["get", "attachment", attachment_id, "--itemid", item_id, "--raw"])
+        .output()
+        .context("Failed to get Bitwarden attachment")?;
+
+    if !get_output.status.success() {
+        anyhow::bail!(
+            "Bitwarden CLI failed to download attachment: {}",
+            String::from_utf8_lossy(&get_output.stderr).trim()
+        );
+    }
+
+    Ok(get_output.stdout)
+}
+
+/// Delete an attachment from the given item
+pub fn delete_attachment(item_id: &str, attachment_id: &str) -> Result<()> {
+    let delete_status = Command::new("bw")
+        .args(["delete", "attachment", attachment_id, "--itemid", item_id])
+        .output()
+        .context("Failed to delete Bitwarden attachment")?;
+
+    if !delete_status.status.success() {
+        anyhow::bail!("Bitwarden CLI failed to delete attachment '{}'", attachment_id);
+    }
+
+    Ok(())
+}
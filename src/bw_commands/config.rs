@@ -0,0 +1,16 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Get the server URL the Bitwarden CLI is configured for (`bw config server`)
+pub fn get_server_url() -> Result<String> {
+    let output = Command::new("bw")
+        .args(["config", "server"])
+        .output()
+        .context("Failed to execute Bitwarden CLI")?;
+
+    if !output.status.success() {
+        anyhow::bail!("Bitwarden CLI failed to report the configured server");
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
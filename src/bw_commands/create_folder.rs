@@ -1,11 +1,11 @@
 use anyhow::{Result, Context};
 use std::process::Command;
-use std::io::Write;
+use crate::bw_commands::encode::encode_json;
 use crate::bw_commands::get_template::{get_template, TemplateType};
 
 /// Create a folder in Bitwarden with the given name
 pub fn create_folder(name: &str) -> Result<String> {
-    println!("Creating folder '{}'...", name);
+    eprintln!("Creating folder '{}'...", name);
     
     // Get the folder template
     let mut template = get_template(TemplateType::Folder)?;
@@ -13,36 +13,12 @@ pub fn create_folder(name: &str) -> Result<String> {
     // Set the folder name
     template["name"] = serde_json::Value::String(name.to_string());
     
-    let json_str = serde_json::to_string(&template)
-        .context("Failed to serialize folder template")?;
-    
     // Encode the JSON
-    let mut encode_child = Command::new("bw")
-        .arg("encode")
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .spawn()
-        .context("Failed to spawn bw encode for folder")?;
-    
-    {
-        let stdin = encode_child.stdin.as_mut().unwrap();
-        stdin.write_all(json_str.as_bytes())
-            .context("Failed to write to bw encode stdin for folder")?;
-    }
-    
-    let encode_result = encode_child.wait_with_output()
-        .context("Failed to wait for bw encode for folder")?;
-    
-    if !encode_result.status.success() {
-        anyhow::bail!("Failed to encode folder");
-    }
-    
-    let encoded_data = String::from_utf8(encode_result.stdout)
-        .context("Failed to parse encoded folder data")?;
+    let encoded_data = encode_json(&template)?;
     
     // Create the folder
     let create_output = Command::new("bw")
-        .args(["create", "folder", encoded_data.trim()])
+        .args(["create", "folder", &encoded_data])
         .output()
         .context("Failed to create Bitwarden folder")?;
     
@@ -55,7 +31,7 @@ pub fn create_folder(name: &str) -> Result<String> {
         .context("Failed to parse created folder JSON")?;
     
     if let Some(id) = created_folder["id"].as_str() {
-        println!("Created folder '{}' successfully with ID: {}", name, id);
+        eprintln!("Created folder '{}' successfully with ID: {}", name, id);
         Ok(id.to_string())
     } else {
         anyhow::bail!("Failed to get folder ID from created folder response");
@@ -100,7 +76,7 @@ pub fn find_folder_by_name(name: &str) -> Result<Option<String>> {
 pub fn ensure_folder_exists(name: &str) -> Result<String> {
     // First, try to find existing folder
     if let Some(existing_id) = find_folder_by_name(name)? {
-        println!("Found existing folder '{}'.", name);
+        eprintln!("Found existing folder '{}'.", name);
         return Ok(existing_id);
     }
     
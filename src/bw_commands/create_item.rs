@@ -1,11 +1,16 @@
+use crate::bw_commands::encode::encode_json;
 use crate::bw_commands::get_template::{get_template, TemplateType};
 use anyhow::{Context, Result};
-use std::io::Write;
 use std::process::Command;
 
-/// Create a secure note item in Bitwarden
-pub fn create_item(name: &str, notes: &str, folder_id: &str) -> Result<String> {
-    println!("Creating item '{}'...", name);
+/// Create a secure note item in Bitwarden with the given notes and custom fields
+pub fn create_item(
+    name: &str,
+    notes: &str,
+    fields: Vec<serde_json::Value>,
+    folder_id: &str,
+) -> Result<serde_json::Value> {
+    eprintln!("Creating item '{}'...", name);
 
     // Get the item template
     let mut template = get_template(TemplateType::Item)?;
@@ -16,53 +21,32 @@ pub fn create_item(name: &str, notes: &str, folder_id: &str) -> Result<String> {
     template["notes"] = serde_json::Value::String(notes.to_string());
     template["name"] = serde_json::Value::String(name.to_string());
     template["folderId"] = serde_json::Value::String(folder_id.to_string());
-
-    // Convert to JSON string
-    let json_str = serde_json::to_string(&template).context("Failed to serialize template")?;
+    template["fields"] = serde_json::Value::Array(fields);
 
     // Encode the JSON
-    let mut encode_child = Command::new("bw")
-        .arg("encode")
-        .stdin(std::process::Stdio::piped())
-        .stdout(std::process::Stdio::piped())
-        .spawn()
-        .context("Failed to spawn bw encode")?;
-
-    {
-        let stdin = encode_child.stdin.as_mut().unwrap();
-        stdin
-            .write_all(json_str.as_bytes())
-            .context("Failed to write to bw encode stdin")?;
-    }
-
-    let encode_result = encode_child
-        .wait_with_output()
-        .context("Failed to wait for bw encode")?;
-
-    if !encode_result.status.success() {
-        anyhow::bail!("Failed to encode item");
-    }
-
-    let encoded_data =
-        String::from_utf8(encode_result.stdout).context("Failed to parse encoded data")?;
+    let encoded_data = encode_json(&template)?;
 
     // Create the item
     let create_output = Command::new("bw")
-        .args(["create", "item", encoded_data.trim()])
+        .args(["create", "item", &encoded_data])
         .output()
         .context("Failed to create Bitwarden item")?;
 
     if !create_output.status.success() {
-        anyhow::bail!("Bitwarden CLI failed to store item");
+        anyhow::bail!(
+            "Bitwarden CLI failed to store item '{}': {}",
+            name,
+            String::from_utf8_lossy(&create_output.stderr).trim()
+        );
     }
 
-    // Parse the created item response to get the ID
+    // Parse the created item response to confirm the ID
     let created_item: serde_json::Value = serde_json::from_slice(&create_output.stdout)
         .context("Failed to parse created item JSON")?;
 
     if let Some(id) = created_item["id"].as_str() {
-        println!("Created item '{}' successfully with ID: {}", name, id);
-        Ok(id.to_string())
+        eprintln!("Created item '{}' successfully with ID: {}", name, id);
+        Ok(created_item)
     } else {
         anyhow::bail!("Failed to get item ID from created item response");
     }
@@ -0,0 +1,20 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Move an item to the Bitwarden trash
+pub fn delete_item(item_id: &str) -> Result<()> {
+    let delete_output = Command::new("bw")
+        .args(["delete", "item", item_id])
+        .output()
+        .context("Failed to delete Bitwarden item")?;
+
+    if !delete_output.status.success() {
+        anyhow::bail!(
+            "Bitwarden CLI failed to delete item '{}': {}",
+            item_id,
+            String::from_utf8_lossy(&delete_output.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
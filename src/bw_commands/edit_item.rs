@@ -0,0 +1,35 @@
+use crate::bw_commands::encode::encode_json;
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Replace an existing Bitwarden item with the given item JSON
+pub fn edit_item(item_id: &str, item: &serde_json::Value) -> Result<serde_json::Value> {
+    let name = item["name"].as_str().unwrap_or("(unnamed)");
+    eprintln!("Updating item '{}'...", name);
+
+    let encoded_data = encode_json(item)?;
+
+    let edit_output = Command::new("bw")
+        .args(["edit", "item", item_id, &encoded_data])
+        .output()
+        .context("Failed to edit Bitwarden item")?;
+
+    if !edit_output.status.success() {
+        anyhow::bail!(
+            "Bitwarden CLI failed to update item '{}': {}",
+            name,
+            String::from_utf8_lossy(&edit_output.stderr).trim()
+        );
+    }
+
+    // Parse the edited item response to confirm the ID
+    let edited_item: serde_json::Value = serde_json::from_slice(&edit_output.stdout)
+        .context("Failed to parse edited item JSON")?;
+
+    if let Some(id) = edited_item["id"].as_str() {
+        eprintln!("Updated item '{}' successfully with ID: {}", name, id);
+        Ok(edited_item)
+    } else {
+        anyhow::bail!("Failed to get item ID from edited item response");
+    }
+}
@@ -0,0 +1,35 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::Command;
+
+/// Serialize a JSON value and run it through `bw encode` for create/edit commands
+pub fn encode_json(value: &serde_json::Value) -> Result<String> {
+    let json_str = serde_json::to_string(value).context("Failed to serialize JSON for bw encode")?;
+
+    let mut encode_child = Command::new("bw")
+        .arg("encode")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .context("Failed to spawn bw encode")?;
+
+    {
+        let stdin = encode_child.stdin.as_mut().unwrap();
+        stdin
+            .write_all(json_str.as_bytes())
+            .context("Failed to write to bw encode stdin")?;
+    }
+
+    let encode_result = encode_child
+        .wait_with_output()
+        .context("Failed to wait for bw encode")?;
+
+    if !encode_result.status.success() {
+        anyhow::bail!("Failed to encode JSON");
+    }
+
+    let encoded_data =
+        String::from_utf8(encode_result.stdout).context("Failed to parse encoded data")?;
+
+    Ok(encoded_data.trim().to_string())
+}
@@ -0,0 +1,16 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Fetch a single item by id, straight from Bitwarden
+pub fn get_item(item_id: &str) -> Result<serde_json::Value> {
+    let output_json = Command::new("bw")
+        .args(["get", "item", item_id])
+        .output()
+        .context("Failed to execute Bitwarden CLI")?;
+
+    if !output_json.status.success() {
+        anyhow::bail!("Bitwarden CLI failed to retrieve item '{}'.", item_id);
+    }
+
+    serde_json::from_slice(&output_json.stdout).context("Failed to parse Bitwarden item JSON")
+}
@@ -0,0 +1,19 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// List all items stored in the given Bitwarden folder
+pub fn list_items(folder_id: &str) -> Result<Vec<serde_json::Value>> {
+    let items_output = Command::new("bw")
+        .args(["list", "items", "--folderid", folder_id])
+        .output()
+        .context("Failed to list items in Bitwarden folder")?;
+
+    if !items_output.status.success() {
+        anyhow::bail!("Failed to list items for folder '{}'.", folder_id);
+    }
+
+    let items: Vec<serde_json::Value> = serde_json::from_slice(&items_output.stdout)
+        .context("Failed to parse items JSON")?;
+
+    Ok(items)
+}
@@ -1,9 +1,24 @@
 pub mod sync;
 pub mod get_template;
+pub mod encode;
 pub mod create_folder;
 pub mod create_item;
+pub mod edit_item;
+pub mod get_item;
+pub mod delete_item;
+pub mod list_items;
+pub mod attachment;
+pub mod status;
+pub mod config;
 
 pub use sync::sync_vault;
 pub use create_folder::ensure_folder_exists;
 pub use create_folder::find_folder_by_name;
-pub use create_item::create_item;
\ No newline at end of file
+pub use create_item::create_item;
+pub use edit_item::edit_item;
+pub use get_item::get_item;
+pub use delete_item::delete_item;
+pub use list_items::list_items;
+pub use status::get_status;
+pub use config::get_server_url;
+pub use attachment::{create_attachment, delete_attachment, get_attachment};
@@ -0,0 +1,16 @@
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Get the Bitwarden CLI status (server, user email, lock state)
+pub fn get_status() -> Result<serde_json::Value> {
+    let status_output = Command::new("bw")
+        .arg("status")
+        .output()
+        .context("Failed to get Bitwarden status")?;
+
+    if !status_output.status.success() {
+        anyhow::bail!("Failed to get Bitwarden status");
+    }
+
+    serde_json::from_slice(&status_output.stdout).context("Failed to parse status JSON")
+}
@@ -1,15 +1,17 @@
 use anyhow::{Context, Result};
-use std::process::Command;
+use std::process::{Command, Stdio};
 use crate::auth::ensure_bw_cli_available;
 
 /// Sync with Bitwarden server to ensure we have the latest data
 pub fn sync_vault() -> Result<()> {
     ensure_bw_cli_available()?;
 
-    println!("Syncing with Bitwarden server...");
+    eprintln!("Syncing with Bitwarden server...");
     
+    // Keep stdout free for command output, e.g. `eval "$(bw-env export)"`
     let sync_status = Command::new("bw")
         .arg("sync")
+        .stdout(Stdio::from(std::io::stderr()))
         .status()
         .context("Failed to run bw sync")?;
     
@@ -17,6 +19,6 @@ pub fn sync_vault() -> Result<()> {
         anyhow::bail!("Failed to sync with Bitwarden server");
     }
     
-    println!("Sync completed successfully.");
+    eprintln!("Sync completed successfully.");
     Ok(())
 }
\ No newline at end of file
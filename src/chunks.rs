@@ -0,0 +1,254 @@
+use crate::bw_commands::{create_item, delete_item, get_item};
+use crate::content::{layout_field, Layout, MAX_NOTES_BYTES};
+use crate::fields::{get_fields, new_field, remove_fields, FIELD_TYPE_TEXT};
+use anyhow::{anyhow, Context, Result};
+use base64::Engine;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+
+// Comma-separated ids of the chunk items, in order
+const CHUNKS_FIELD_NAME: &str = "bw-env:chunks";
+
+// Set on every chunk item, holding the name of the item it belongs to
+const CHUNK_OF_FIELD_NAME: &str = "bw-env:chunk-of";
+
+// Hex SHA-256 of the original content, checked after reassembly
+const CHECKSUM_FIELD_NAME: &str = "bw-env:sha256";
+
+/// Whether the item only holds a piece of a larger, chunked item
+pub fn is_chunk(item: &Value) -> bool {
+    !get_fields(item, CHUNK_OF_FIELD_NAME).is_empty()
+}
+
+/// Hex-encoded SHA-256 of the given content
+pub fn sha256_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Compress the content and spread it across linked chunk items.
+///
+/// Returns the notes and custom fields for the item that links the chunks together.
+pub fn store_chunks(
+    item_name: &str,
+    content: &str,
+    folder_id: &str,
+) -> Result<(String, Vec<Value>)> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(content.as_bytes())
+        .context("Failed to compress content")?;
+    let compressed = encoder.finish().context("Failed to compress content")?;
+    let encoded = base64::engine::general_purpose::STANDARD.encode(compressed);
+
+    // Base64 is ASCII, so splitting on byte boundaries is safe
+    let pieces: Vec<&str> = encoded
+        .as_bytes()
+        .chunks(MAX_NOTES_BYTES)
+        .map(|piece| std::str::from_utf8(piece).unwrap_or_default())
+        .collect();
+
+    println!(
+        "Content is {} bytes, above the secure note limit; storing it compressed across {} linked item(s).",
+        content.len(),
+        pieces.len()
+    );
+
+    let mut chunk_ids = Vec::with_capacity(pieces.len());
+    for (idx, piece) in pieces.iter().enumerate() {
+        let chunk_name = format!("{} [chunk {}/{}]", item_name, idx + 1, pieces.len());
+        let fields = vec![new_field(CHUNK_OF_FIELD_NAME, item_name, FIELD_TYPE_TEXT)];
+        let created = create_item(&chunk_name, piece, fields, folder_id).and_then(|created| {
+            created["id"]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("Missing chunk item id"))
+        });
+        match created {
+            Ok(chunk_id) => chunk_ids.push(chunk_id),
+            Err(err) => {
+                // `list` hides chunk items, so never leave incomplete ones behind
+                delete_chunk_ids(&chunk_ids);
+                return Err(err);
+            }
+        }
+    }
+
+    let fields = vec![
+        layout_field(Layout::Chunked),
+        new_field(CHUNKS_FIELD_NAME, &chunk_ids.join(","), FIELD_TYPE_TEXT),
+        new_field(
+            CHECKSUM_FIELD_NAME,
+            &sha256_hex(content.as_bytes()),
+            FIELD_TYPE_TEXT,
+        ),
+    ];
+    Ok((String::new(), fields))
+}
+
+/// Fetch, reassemble and verify the content of a chunked item
+pub fn read_chunks(item: &Value) -> Result<String> {
+    let name = item["name"].as_str().unwrap_or("(unnamed)");
+
+    let mut encoded = String::new();
+    for chunk_id in chunk_ids(item) {
+        let chunk = get_item(&chunk_id)
+            .with_context(|| format!("Failed to fetch a chunk of '{}'", name))?;
+        encoded.push_str(chunk["notes"].as_str().unwrap_or(""));
+    }
+
+    let compressed = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .with_context(|| format!("Chunks of '{}' are not valid base64", name))?;
+    let mut content = String::new();
+    DeflateDecoder::new(compressed.as_slice())
+        .read_to_string(&mut content)
+        .with_context(|| format!("Failed to decompress the chunks of '{}'", name))?;
+
+    let expected = get_fields(item, CHECKSUM_FIELD_NAME)
+        .first()
+        .copied()
+        .unwrap_or("");
+    if sha256_hex(content.as_bytes()) != expected {
+        anyhow::bail!(
+            "Checksum mismatch while reassembling '{}'; a chunk item may be missing or modified",
+            name
+        );
+    }
+
+    Ok(content)
+}
+
+/// Remove the fields linking an item to its chunks
+pub fn clear_chunk_fields(item: &mut Value) {
+    remove_fields(item, CHUNKS_FIELD_NAME);
+    remove_fields(item, CHECKSUM_FIELD_NAME);
+}
+
+/// Delete the chunk items linked from a chunked item
+pub fn delete_chunks(item: &Value) -> Result<()> {
+    for chunk_id in chunk_ids(item) {
+        delete_item(&chunk_id)?;
+    }
+    Ok(())
+}
+
+/// Delete the chunk items created for content whose item could not be saved.
+///
+/// `fields` are the ones returned by `store_chunks`; anything else is ignored.
+pub fn discard_new_chunks(fields: &[Value]) {
+    delete_chunk_ids(&chunk_ids(&serde_json::json!({ "fields": fields })));
+}
+
+/// Best-effort cleanup of chunk items, warning about those that could not be deleted
+fn delete_chunk_ids(chunk_ids: &[String]) {
+    for chunk_id in chunk_ids {
+        if let Err(err) = delete_item(chunk_id) {
+            eprintln!("Warning: failed to remove chunk item '{}': {}", chunk_id, err);
+        }
+    }
+}
+
+fn chunk_ids(item: &Value) -> Vec<String> {
+    get_fields(item, CHUNKS_FIELD_NAME)
+        .first()
+        .map(|ids| {
+            ids.split(',')
+                .filter(|id| !id.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::content::layout_of;
+    use crate::test_support::FakeBw;
+
+    /// Content that barely compresses, so it needs several chunks
+    fn noisy_content(len: usize) -> String {
+        let mut state: u32 = 1;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                char::from(b'!' + (state >> 16) as u8 % 90)
+            })
+            .collect()
+    }
+
+    fn chunked_item(fields: Vec<Value>) -> Value {
+        serde_json::json!({ "id": "env-1", "name": "acme/api/.env", "notes": "", "fields": fields })
+    }
+
+    #[test]
+    fn chunks_are_compressed_encoded_and_checksummed() {
+        let bw = FakeBw::new();
+        let content = noisy_content(3 * MAX_NOTES_BYTES);
+
+        let (notes, fields) = store_chunks("acme/api/.env", &content, "folder-1").unwrap();
+        let item = chunked_item(fields);
+
+        assert_eq!(notes, "");
+        assert_eq!(layout_of(&item), Layout::Chunked);
+        let ids = chunk_ids(&item);
+        assert!(ids.len() > 1);
+        let mut encoded = String::new();
+        for id in &ids {
+            let chunk = bw.item(id).unwrap();
+            assert!(is_chunk(&chunk));
+            assert!(chunk["notes"].as_str().unwrap().len() <= MAX_NOTES_BYTES);
+            encoded.push_str(chunk["notes"].as_str().unwrap());
+        }
+        let compressed = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .unwrap();
+        let mut inflated = String::new();
+        DeflateDecoder::new(compressed.as_slice())
+            .read_to_string(&mut inflated)
+            .unwrap();
+        assert_eq!(inflated, content);
+        assert_eq!(
+            get_fields(&item, CHECKSUM_FIELD_NAME),
+            vec![sha256_hex(content.as_bytes())]
+        );
+
+        assert_eq!(read_chunks(&item).unwrap(), content);
+    }
+
+    #[test]
+    fn reassembly_fails_on_a_checksum_mismatch() {
+        let _bw = FakeBw::new();
+        let (_, mut fields) = store_chunks("acme/api/.env", "A=1\n", "folder-1").unwrap();
+        for field in &mut fields {
+            if field["name"] == CHECKSUM_FIELD_NAME {
+                field["value"] = Value::from(sha256_hex(b"A=2\n"));
+            }
+        }
+
+        let err = read_chunks(&chunked_item(fields)).unwrap_err();
+
+        assert!(err.to_string().contains("Checksum mismatch"), "{:#}", err);
+    }
+
+    #[test]
+    fn failed_chunk_reports_why_and_leaves_no_chunks_behind() {
+        let bw = FakeBw::new();
+        bw.fail_after("create", "item", 1);
+
+        let err = store_chunks(
+            "acme/api/.env",
+            &noisy_content(3 * MAX_NOTES_BYTES),
+            "folder-1",
+        )
+        .unwrap_err();
+
+        assert!(err.to_string().contains("fake failure"), "{:#}", err);
+        assert!(bw.calls().contains(&"delete item".to_string()));
+        assert!(bw.item_ids().is_empty());
+    }
+}
@@ -1,3 +1,6 @@
+use crate::commands::export::ExportFormat;
+use crate::commands::list::{ListFormat, ListSort};
+use crate::content::Layout;
 use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
@@ -6,6 +9,12 @@ use clap::{Parser, Subcommand};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+    /// Unlock the vault with the master password in this environment variable, without prompting
+    #[arg(long, global = true, value_name = "VAR", conflicts_with = "passwordfile")]
+    pub passwordenv: Option<String>,
+    /// Unlock the vault with the master password in the first line of this file, without prompting
+    #[arg(long, global = true, value_name = "PATH")]
+    pub passwordfile: Option<String>,
 }
 
 #[derive(Subcommand)]
@@ -14,9 +23,158 @@ pub enum Commands {
     Store {
         /// Path to the .env file
         path: String,
+        /// How to lay out the file on the item (defaults to the existing item's layout, or notes)
+        #[arg(long, value_enum)]
+        layout: Option<Layout>,
+        /// Encrypt the content with a passphrase (via `age`) before storing it
+        #[arg(long, conflicts_with = "recipients")]
+        encrypt: bool,
+        /// Encrypt the content for an age public key before storing it (repeatable)
+        #[arg(long = "recipient", value_name = "AGE_PUBLIC_KEY")]
+        recipients: Vec<String>,
+        /// Store the content without client-side encryption, removing it from an existing item
+        #[arg(long, conflicts_with_all = ["encrypt", "recipients"])]
+        no_encrypt: bool,
     },
     /// Retrieve a .env file from Bitwarden
-    Retrieve,
-    /// List all .env files stored in Bitwarden
-    List,
+    Retrieve {
+        /// Item name, name glob (e.g. 'acme/*/.env') or id to download without prompting
+        item: Option<String>,
+        /// Where to write the file (defaults to its path in the current repository, or its file
+        /// name in the current directory for items of other repositories)
+        #[arg(long, short, requires = "item")]
+        output: Option<String>,
+        /// Write files to the current directory under their file name only, instead of
+        /// their original path in the repository
+        #[arg(long)]
+        flat: bool,
+        /// Overwrite local files that differ from the vault copy without asking
+        #[arg(long, conflicts_with_all = ["backup", "skip"])]
+        force: bool,
+        /// Back up local files that differ from the vault copy before overwriting them
+        #[arg(long, conflicts_with = "skip")]
+        backup: bool,
+        /// Keep local files that differ from the vault copy without asking
+        #[arg(long)]
+        skip: bool,
+        /// Only show items whose provenance matches KEY=VALUE (repeatable), e.g. branch=main
+        #[arg(long = "where", value_name = "KEY=VALUE")]
+        filters: Vec<String>,
+    },
+    /// List the .env files stored in Bitwarden (those of the current repository, when inside one)
+    List {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ListFormat::Text)]
+        format: ListFormat,
+        /// Only list items of this repository (OWNER/REPO; defaults to the current repository)
+        #[arg(long, value_name = "OWNER/REPO", conflicts_with = "all_repos")]
+        repo: Option<String>,
+        /// List items of every repository, even inside one
+        #[arg(long)]
+        all_repos: bool,
+        /// Only list items whose name matches this glob (e.g. '*/.env.production')
+        #[arg(long, value_name = "GLOB")]
+        name: Option<String>,
+        /// Only list items modified on or after this date
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        since: Option<String>,
+        /// Only list items in this folder
+        #[arg(long)]
+        folder: Option<String>,
+        /// Sort by name, or newest first by creation or modification date
+        #[arg(long, value_enum)]
+        sort: Option<ListSort>,
+        /// Nest items by owner, repository and directory, with counts per directory
+        #[arg(long, conflicts_with = "format")]
+        tree: bool,
+        /// Only show items whose provenance matches KEY=VALUE (repeatable), e.g. branch=main
+        #[arg(long = "where", value_name = "KEY=VALUE")]
+        filters: Vec<String>,
+    },
+    /// Compare a local .env file with its copy in Bitwarden (exits with 1 when they differ)
+    Diff {
+        /// Path to the local .env file
+        path: String,
+        /// Item name or id to compare against (defaults to the name `store` would use)
+        #[arg(long)]
+        item: Option<String>,
+        /// Show values instead of masking them
+        #[arg(long)]
+        reveal: bool,
+    },
+    /// Merge a local .env file with its copy in Bitwarden when both have changed
+    Merge {
+        /// Path to the local .env file
+        path: String,
+        /// Item name or id to merge with (defaults to the name `store` would use)
+        #[arg(long)]
+        item: Option<String>,
+        /// Write conflict markers into the file instead of resolving conflicts interactively
+        #[arg(long)]
+        markers: bool,
+    },
+    /// Show previous versions of a stored .env file
+    History {
+        /// Item name or id
+        item: String,
+    },
+    /// Restore a previous version of a stored .env file
+    Rollback {
+        /// Item name or id
+        item: String,
+        /// Version number to restore, as shown by `history` (1 = the version before the current one)
+        #[arg(long, default_value_t = 1)]
+        to: usize,
+    },
+    /// Run a command with the variables of a stored .env file, without writing it to disk
+    Run {
+        /// Item name or id (defaults to the .env item for the current directory of this repository)
+        #[arg(long)]
+        item: Option<String>,
+        /// Keep variables that are already set in the environment instead of overriding them
+        #[arg(long)]
+        no_override: bool,
+        /// Start the command with only the stored variables, plus PATH, HOME, USER, LOGNAME,
+        /// SHELL, TERM and LANG from the current environment
+        #[arg(long)]
+        clean: bool,
+        /// Command to run, after `--`
+        #[arg(last = true, required = true, value_name = "COMMAND")]
+        command: Vec<String>,
+    },
+    /// Print the variables of a stored .env file in a shell or tool format
+    Export {
+        /// Item name or id (defaults to the .env item for the current directory of this repository)
+        item: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = ExportFormat::Posix)]
+        format: ExportFormat,
+    },
+    /// Open a stored item in the web vault of the configured server (see `BW_ENV_VAULT_URL`)
+    Open {
+        /// Item name or id (defaults to the .env item for the current directory of this repository)
+        item: Option<String>,
+    },
+    /// Unlock the vault and reuse the session in later invocations, until it expires or `lock`
+    Unlock {
+        /// Seconds to reuse the session for
+        #[arg(long, default_value_t = 3600)]
+        ttl: u64,
+    },
+    /// Lock the vault and forget the session cached by `unlock`
+    Lock,
+    /// Print the current repository's variables for direnv (see `--stdlib` for `use bw_env`)
+    Direnv {
+        /// Item name or id (defaults to the .env item for the current directory of this repository)
+        item: Option<String>,
+        /// Seconds to reuse cached variables without contacting Bitwarden
+        #[arg(long, default_value_t = 900)]
+        ttl: u64,
+        /// Ignore cached variables and fetch them again
+        #[arg(long)]
+        refresh: bool,
+        /// Print the direnv stdlib snippet providing `use bw_env`
+        #[arg(long)]
+        stdlib: bool,
+    },
 }
\ No newline at end of file
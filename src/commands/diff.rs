@@ -0,0 +1,85 @@
+use crate::bw_commands::{find_folder_by_name, list_items, sync_vault};
+use crate::commands::retrieve::{fetch_content, find_item};
+use crate::commands::store::find_item_for_file;
+use crate::content::ensure_dotenv_layout;
+use crate::dotenv::{diff_keys, to_map};
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+
+const ROOT_FOLDER_NAME: &str = "bw-env";
+
+// Shown instead of values unless --reveal is passed
+const MASKED_VALUE: &str = "********";
+
+/// Compare a local .env file with its vault copy; returns whether they differ
+pub fn diff_env(path: &str, item: Option<&str>, reveal: bool) -> Result<bool> {
+    sync_vault()?;
+
+    let local = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read .env file at {}", path))?;
+
+    let folder_id = find_folder_by_name(ROOT_FOLDER_NAME)?
+        .ok_or_else(|| anyhow!("No '{}' folder found in Bitwarden", ROOT_FOLDER_NAME))?;
+    let items = list_items(&folder_id)?;
+    let item = match item {
+        Some(name) => find_item(&items, name)?,
+        None => find_item_for_file(&items, path)?,
+    };
+    ensure_dotenv_layout(item)?;
+    let remote = fetch_content(item)?;
+
+    println!(
+        "\nComparing {} (local) with '{}' (vault):\n",
+        path,
+        item["name"].as_str().unwrap_or("(unnamed)")
+    );
+
+    if !print_changes(&local, &remote, reveal) {
+        println!("No differences.");
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Print the key-level changes from the vault copy to the local content, masking values
+/// unless `reveal`; returns whether any key differs
+pub(crate) fn print_changes(local: &str, remote: &str, reveal: bool) -> bool {
+    // Vault is the old side, the local file the new one: "added" means only present locally
+    let changes = diff_keys(remote, local);
+    let local_map = to_map(local);
+    let remote_map = to_map(remote);
+    let show = |value: &str| {
+        if reveal {
+            value.to_string()
+        } else {
+            MASKED_VALUE.to_string()
+        }
+    };
+
+    if changes.is_empty() {
+        return false;
+    }
+
+    for key in &changes.added {
+        println!("+ {}={}  (only local)", key, show(&local_map[key]));
+    }
+    for key in &changes.removed {
+        println!("- {}={}  (only in vault)", key, show(&remote_map[key]));
+    }
+    for key in &changes.changed {
+        println!(
+            "~ {}: {} (vault) -> {} (local)",
+            key,
+            show(&remote_map[key]),
+            show(&local_map[key])
+        );
+    }
+
+    println!(
+        "\n{} added, {} removed, {} changed.",
+        changes.added.len(),
+        changes.removed.len(),
+        changes.changed.len()
+    );
+    true
+}
@@ -0,0 +1,125 @@
+use crate::auth::{unlock_vault, PasswordSource};
+use crate::bw_commands::{find_folder_by_name, list_items, sync_vault};
+use crate::chunks::{is_chunk, sha256_hex};
+use crate::commands::export::{format_variables, variables, ExportFormat};
+use crate::commands::retrieve::fetch_content;
+use crate::commands::run::{context_dir, resolve_item};
+use crate::content::ensure_dotenv_layout;
+use crate::secure_file::{create_private_dir, runtime_dir, write_secret_file};
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const ROOT_FOLDER_NAME: &str = "bw-env";
+
+// Sourced by direnv from ~/.config/direnv/lib/, enabling `use bw_env [args]` in .envrc
+const STDLIB_SNIPPET: &str = r#"# bw-env integration for direnv: add `use bw_env` to your .envrc
+use_bw_env() {
+  local exports
+  exports="$(bw-env direnv "$@")" || return $?
+  eval "$exports"
+}
+"#;
+
+/// Print `export` statements for the current repository's .env item, for direnv.
+///
+/// Results are cached for `ttl` seconds without calling `bw` at all. After that the vault
+/// is synced, and the item is only fetched again when its revisionDate changed.
+pub fn direnv_env(
+    item: Option<&str>,
+    ttl: u64,
+    refresh: bool,
+    stdlib: bool,
+    password: Option<&PasswordSource>,
+) -> Result<()> {
+    if stdlib {
+        print!("{}", STDLIB_SNIPPET);
+        return Ok(());
+    }
+
+    let key = match item {
+        Some(item) => item.to_string(),
+        None => context_dir().ok_or_else(|| {
+            anyhow!("Not inside a GitHub repository; pass the item name or id to `use bw_env`")
+        })?,
+    };
+    let cache_path = cache_path(&key);
+    let cached = cache_path.as_deref().and_then(load_cache);
+
+    let fresh = cached.as_ref().filter(|cache| {
+        let fetched_at = cache["fetchedAt"].as_u64().unwrap_or(0);
+        !refresh && now().saturating_sub(fetched_at) < ttl
+    });
+    if let Some(cache) = fresh {
+        return print_exports(cache);
+    }
+
+    unlock_vault(password)?;
+    sync_vault()?;
+    let folder_id = find_folder_by_name(ROOT_FOLDER_NAME)?
+        .ok_or_else(|| anyhow!("No '{}' folder found in Bitwarden", ROOT_FOLDER_NAME))?;
+    let mut items = list_items(&folder_id)?;
+    items.retain(|item| !is_chunk(item));
+    let item = resolve_item(&items, item)?;
+    ensure_dotenv_layout(item)?;
+
+    // The cached values are still current when the item has not been revised since
+    let unchanged = cached.as_ref().filter(|cache| {
+        !refresh && cache["itemId"] == item["id"] && cache["revisionDate"] == item["revisionDate"]
+    });
+    let variables = match unchanged {
+        Some(cache) => cache["variables"].clone(),
+        None => serde_json::to_value(variables(&fetch_content(item)?))?,
+    };
+
+    let cache = serde_json::json!({
+        "itemId": item["id"],
+        "revisionDate": item["revisionDate"],
+        "fetchedAt": now(),
+        "variables": variables,
+    });
+    if let Some(cache_path) = &cache_path {
+        save_cache(cache_path, &cache);
+    }
+    print_exports(&cache)
+}
+
+fn print_exports(cache: &serde_json::Value) -> Result<()> {
+    let variables: Vec<(String, String)> =
+        serde_json::from_value(cache["variables"].clone()).context("Invalid cached variables")?;
+    print!("{}", format_variables(&variables, ExportFormat::Posix)?);
+    Ok(())
+}
+
+/// Cache file for an item or context; it holds secret values, so it only lives in the
+/// per-user runtime directory
+fn cache_path(key: &str) -> Option<PathBuf> {
+    let file_name = format!("{}.json", &sha256_hex(key.as_bytes())[..16]);
+    Some(runtime_dir()?.join("direnv").join(file_name))
+}
+
+fn load_cache(path: &Path) -> Option<serde_json::Value> {
+    let bytes = fs::read(path).ok()?;
+    serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .filter(|cache| cache["variables"].is_array())
+}
+
+fn save_cache(path: &Path, cache: &serde_json::Value) {
+    let written = path
+        .parent()
+        .map_or(Ok(()), create_private_dir)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| write_secret_file(path, cache.to_string().as_bytes()));
+    if let Err(err) = written {
+        eprintln!("Warning: failed to cache variables in {:?}: {}", path, err);
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,322 @@
+use crate::bw_commands::{find_folder_by_name, list_items, sync_vault};
+use crate::chunks::is_chunk;
+use crate::commands::retrieve::fetch_content;
+use crate::commands::run::resolve_item;
+use crate::content::ensure_dotenv_layout;
+use crate::dotenv::parse;
+use anyhow::{anyhow, Result};
+use clap::ValueEnum;
+
+const ROOT_FOLDER_NAME: &str = "bw-env";
+
+/// Output formats for `export`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// `export KEY='value'` for sh, bash and zsh
+    Posix,
+    /// `set -gx KEY 'value'` for fish
+    Fish,
+    /// `$env:KEY = 'value'` for PowerShell
+    Powershell,
+    /// A JSON object
+    Json,
+    /// A YAML mapping
+    Yaml,
+    /// `KEY=value` lines for `docker run --env-file` (no quoting; single-line values only)
+    Docker,
+    /// `KEY="value"` lines for a systemd `EnvironmentFile`
+    Systemd,
+}
+
+/// Print the variables of a stored .env file to stdout in the given format
+pub fn export_env(item: Option<&str>, format: ExportFormat) -> Result<()> {
+    sync_vault()?;
+    let folder_id = find_folder_by_name(ROOT_FOLDER_NAME)?
+        .ok_or_else(|| anyhow!("No '{}' folder found in Bitwarden", ROOT_FOLDER_NAME))?;
+    let mut items = list_items(&folder_id)?;
+    items.retain(|item| !is_chunk(item));
+
+    let item = resolve_item(&items, item)?;
+    ensure_dotenv_layout(item)?;
+    let content = fetch_content(item)?;
+
+    print!("{}", format_variables(&variables(&content), format)?);
+    Ok(())
+}
+
+/// Variables in file order; a key assigned twice keeps its first position and last value
+pub(crate) fn variables(content: &str) -> Vec<(String, String)> {
+    let mut variables: Vec<(String, String)> = Vec::new();
+    for (key, value) in parse(content) {
+        match variables.iter_mut().find(|(existing, _)| *existing == key) {
+            Some(entry) => entry.1 = value,
+            None => variables.push((key, value)),
+        }
+    }
+    variables
+}
+
+pub(crate) fn format_variables(
+    variables: &[(String, String)],
+    format: ExportFormat,
+) -> Result<String> {
+    if format == ExportFormat::Json {
+        // Written by hand, as `serde_json::Map` would sort the keys
+        if variables.is_empty() {
+            return Ok("{}\n".to_string());
+        }
+        let entries: Vec<String> = variables
+            .iter()
+            .map(|(key, value)| format!("  {}: {}", json_quote(key), json_quote(value)))
+            .collect();
+        return Ok(format!("{{\n{}\n}}\n", entries.join(",\n")));
+    }
+
+    let mut output = String::new();
+    for (key, value) in variables {
+        let line = match format {
+            ExportFormat::Posix => {
+                require_identifier(key, format)?;
+                format!("export {}={}", key, posix_quote(value))
+            }
+            ExportFormat::Fish => {
+                require_identifier(key, format)?;
+                format!("set -gx {} {}", key, fish_quote(value))
+            }
+            ExportFormat::Powershell => {
+                require_identifier(key, format)?;
+                format!("$env:{} = {}", key, powershell_quote(value))
+            }
+            ExportFormat::Yaml => format!("{}: {}", yaml_key(key), json_quote(value)),
+            ExportFormat::Docker => {
+                // Docker splits on the first `=`, rejects whitespace in names and skips comments
+                if key.is_empty() || key.starts_with('#') || key.contains(['=', ' ', '\t']) {
+                    anyhow::bail!(
+                        "'{}' is not a valid variable name for the docker format",
+                        key
+                    );
+                }
+                // Docker reads values verbatim up to the end of the line
+                if value.contains(['\n', '\r']) {
+                    anyhow::bail!(
+                        "{} has a multi-line value, which docker env files cannot hold",
+                        key
+                    );
+                }
+                format!("{}={}", key, value)
+            }
+            ExportFormat::Systemd => {
+                // systemd ignores assignments whose names are not shell variable names
+                require_identifier(key, format)?;
+                format!("{}={}", key, systemd_quote(value))
+            }
+            ExportFormat::Json => unreachable!(),
+        };
+        output.push_str(&line);
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+/// Fail for keys that the shell cannot use as variable names
+fn require_identifier(key: &str, format: ExportFormat) -> Result<()> {
+    if !is_identifier(key) {
+        anyhow::bail!(
+            "'{}' is not a valid variable name for the {} format",
+            key,
+            format
+                .to_possible_value()
+                .map_or_else(String::new, |value| value.get_name().to_string())
+        );
+    }
+    Ok(())
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Single quotes are fully literal in POSIX shells; a quote is written as `'\''`
+fn posix_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Fish single quotes only interpret `\\` and `\'`
+fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+/// PowerShell single quotes are literal; a quote is doubled, including the typographic
+/// single quotes U+2018 to U+201B, which PowerShell treats the same way
+fn powershell_quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('\'');
+    for c in value.chars() {
+        if matches!(c, '\'' | '\u{2018}'..='\u{201B}') {
+            quoted.push(c);
+        }
+        quoted.push(c);
+    }
+    quoted.push('\'');
+    quoted
+}
+
+/// JSON strings are valid YAML double-quoted scalars
+fn json_quote(value: &str) -> String {
+    serde_json::Value::String(value.to_string()).to_string()
+}
+
+/// Plain keys, unless YAML 1.1 would read them as booleans or null (`yes`, `off`, `null`…)
+fn yaml_key(key: &str) -> String {
+    const RESERVED: [&str; 9] = ["y", "n", "yes", "no", "on", "off", "true", "false", "null"];
+    if is_identifier(key) && !RESERVED.contains(&key.to_ascii_lowercase().as_str()) {
+        key.to_string()
+    } else {
+        json_quote(key)
+    }
+}
+
+/// systemd unescapes `\"`, `\\`, `` \` `` and `\$` inside double quotes and keeps newlines
+fn systemd_quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | '`' | '$') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRICKY: &str = "it's \"quoted\" $HOME `id` \\ ‘curly’\nsecond line";
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn export(pairs: &[(&str, &str)], format: ExportFormat) -> String {
+        format_variables(&vars(pairs), format).unwrap()
+    }
+
+    #[test]
+    fn keeps_the_last_value_at_the_first_position() {
+        assert_eq!(
+            variables("B=1\nA=2\nB=3\n"),
+            vars(&[("B", "3"), ("A", "2")])
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn posix_output_evaluates_to_the_original_value() {
+        let script = format!(
+            "{}printf %s \"$SECRET\"",
+            export(&[("SECRET", TRICKY)], ExportFormat::Posix)
+        );
+        let output = std::process::Command::new("sh")
+            .args(["-c", &script])
+            .output()
+            .unwrap();
+
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), TRICKY);
+    }
+
+    #[test]
+    fn posix_quotes_single_quotes() {
+        assert_eq!(
+            export(&[("A", "it's")], ExportFormat::Posix),
+            "export A='it'\\''s'\n"
+        );
+    }
+
+    #[test]
+    fn fish_escapes_backslashes_and_single_quotes() {
+        assert_eq!(
+            export(&[("A", r"a\b'c")], ExportFormat::Fish),
+            "set -gx A 'a\\\\b\\'c'\n"
+        );
+    }
+
+    #[test]
+    fn powershell_doubles_every_kind_of_single_quote() {
+        assert_eq!(
+            export(&[("A", "it's ‘x’ ‚y‛ $env:HOME")], ExportFormat::Powershell),
+            "$env:A = 'it''s ‘‘x’’ ‚‚y‛‛ $env:HOME'\n"
+        );
+    }
+
+    #[test]
+    fn json_keeps_file_order_and_escapes_values() {
+        assert_eq!(
+            export(&[("Z", "1"), ("A", "a\"b\nc")], ExportFormat::Json),
+            "{\n  \"Z\": \"1\",\n  \"A\": \"a\\\"b\\nc\"\n}\n"
+        );
+        assert_eq!(export(&[], ExportFormat::Json), "{}\n");
+
+        let parsed: serde_json::Value =
+            serde_json::from_str(&export(&[("SECRET", TRICKY)], ExportFormat::Json)).unwrap();
+        assert_eq!(parsed["SECRET"], TRICKY);
+    }
+
+    #[test]
+    fn yaml_quotes_values_and_keyword_keys() {
+        assert_eq!(
+            export(
+                &[("PLAIN", "no"), ("yes", "1"), ("Off", "2"), ("my.key", "3")],
+                ExportFormat::Yaml
+            ),
+            "PLAIN: \"no\"\n\"yes\": \"1\"\n\"Off\": \"2\"\n\"my.key\": \"3\"\n"
+        );
+    }
+
+    #[test]
+    fn docker_writes_values_verbatim_and_rejects_what_it_cannot_hold() {
+        assert_eq!(
+            export(&[("A", "it's \"raw\" $X")], ExportFormat::Docker),
+            "A=it's \"raw\" $X\n"
+        );
+        assert!(format_variables(&vars(&[("A", "a\nb")]), ExportFormat::Docker).is_err());
+        for key in ["", "#A", "A B", "A=B"] {
+            assert!(
+                format_variables(&vars(&[(key, "1")]), ExportFormat::Docker).is_err(),
+                "{:?} should be rejected",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn systemd_escapes_special_characters() {
+        assert_eq!(
+            export(&[("A", "a\"b\\c`d$e")], ExportFormat::Systemd),
+            "A=\"a\\\"b\\\\c\\`d\\$e\"\n"
+        );
+        assert!(format_variables(&vars(&[("1A", "1")]), ExportFormat::Systemd).is_err());
+        assert!(format_variables(&vars(&[("A-B", "1")]), ExportFormat::Systemd).is_err());
+    }
+
+    #[test]
+    fn shells_reject_invalid_variable_names() {
+        for format in [
+            ExportFormat::Posix,
+            ExportFormat::Fish,
+            ExportFormat::Powershell,
+        ] {
+            assert!(format_variables(&vars(&[("MY-VAR", "1")]), format).is_err());
+            assert!(format_variables(&vars(&[("1VAR", "1")]), format).is_err());
+        }
+    }
+}
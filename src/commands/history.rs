@@ -0,0 +1,183 @@
+use crate::bw_commands::{find_folder_by_name, list_items, sync_vault};
+use crate::chunks::is_chunk;
+use crate::commands::retrieve::find_item;
+use crate::commands::store::save_content;
+use crate::content::{
+    ensure_dotenv_layout, inline_content, layout_of, read_content, Layout, MAX_FIELD_BYTES,
+};
+use crate::dotenv::diff_keys;
+use crate::fields::{fields_mut, get_fields, new_field, remove_fields, FIELD_TYPE_HIDDEN};
+use crate::encryption::{encryption_of, is_encrypted};
+use anyhow::{anyhow, Context, Result};
+use std::io::{self, Write};
+
+const ROOT_FOLDER_NAME: &str = "bw-env";
+
+// Hidden custom field holding one previous version per entry, newest first
+const HISTORY_FIELD_NAME: &str = "bw-env:history";
+
+// Keep the item well below Bitwarden's size limits
+const MAX_HISTORY_VERSIONS: usize = 10;
+
+/// A previous version of an item's content
+pub struct Version {
+    pub revision_date: String,
+    pub content: String,
+}
+
+/// Previous versions kept on the item, newest first
+pub(crate) fn previous_versions(item: &serde_json::Value) -> Vec<Version> {
+    get_fields(item, HISTORY_FIELD_NAME)
+        .into_iter()
+        .filter_map(|raw| serde_json::from_str::<serde_json::Value>(raw).ok())
+        .map(|entry| Version {
+            revision_date: entry["revisionDate"].as_str().unwrap_or("unknown").to_string(),
+            content: entry["notes"].as_str().unwrap_or("").to_string(),
+        })
+        .collect()
+}
+
+/// Content the item had at the given revision, if it is still kept
+pub(crate) fn content_at_revision(
+    item: &serde_json::Value,
+    revision_date: &str,
+) -> Result<Option<String>> {
+    if item["revisionDate"].as_str() == Some(revision_date) {
+        return read_content(item).map(Some);
+    }
+    Ok(previous_versions(item)
+        .into_iter()
+        .find(|version| version.revision_date == revision_date)
+        .map(|version| version.content))
+}
+
+/// Record the item's current content as its newest previous version
+pub(crate) fn push_version(item: &mut serde_json::Value) {
+    // Attachments and chunked items are too large to keep in a custom field, and
+    // client-side encrypted content is not versioned
+    let Some(content) = inline_content(item) else {
+        let reason = if is_encrypted(item) {
+            "encrypted client-side"
+        } else if layout_of(item) == Layout::Chunked {
+            "split across chunk items"
+        } else {
+            "stored as attachments"
+        };
+        println!(
+            "Note: '{}' is {}, so its previous version is not kept in the history.",
+            item["name"].as_str().unwrap_or("(unnamed)"),
+            reason
+        );
+        return;
+    };
+    let current = serde_json::json!({
+        "revisionDate": item["revisionDate"].as_str().unwrap_or("unknown"),
+        "notes": content,
+    })
+    .to_string();
+    if current.len() > MAX_FIELD_BYTES {
+        println!("The previous version is too large to keep in the item's history.");
+        return;
+    }
+
+    let older: Vec<String> = get_fields(item, HISTORY_FIELD_NAME)
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+
+    remove_fields(item, HISTORY_FIELD_NAME);
+    let fields = fields_mut(item);
+    for value in std::iter::once(current)
+        .chain(older)
+        .take(MAX_HISTORY_VERSIONS)
+    {
+        fields.push(new_field(HISTORY_FIELD_NAME, &value, FIELD_TYPE_HIDDEN));
+    }
+}
+
+pub fn history_env(query: &str) -> Result<()> {
+    sync_vault()?;
+
+    let items = folder_items()?;
+    let item = find_item(&items, query)?;
+    ensure_dotenv_layout(item)?;
+    let name = item["name"].as_str().unwrap_or("(unnamed)");
+
+    let current = Version {
+        revision_date: item["revisionDate"].as_str().unwrap_or("unknown").to_string(),
+        content: read_content(item)?,
+    };
+    let versions: Vec<Version> = std::iter::once(current)
+        .chain(previous_versions(item))
+        .collect();
+
+    println!("\nHistory for '{}':\n", name);
+    for (idx, version) in versions.iter().enumerate() {
+        let label = if idx == 0 { " (current)" } else { "" };
+        let summary = match versions.get(idx + 1) {
+            Some(older) => diff_keys(&older.content, &version.content).summary(),
+            None => "oldest kept version".to_string(),
+        };
+        println!("{}. {}{} | {}", idx, version.revision_date, label, summary);
+    }
+
+    if versions.len() == 1 {
+        println!("\nNo previous versions stored yet.");
+    }
+    Ok(())
+}
+
+pub fn rollback_env(query: &str, to: usize) -> Result<()> {
+    sync_vault()?;
+
+    let items = folder_items()?;
+    let item = find_item(&items, query)?;
+    ensure_dotenv_layout(item)?;
+    let name = item["name"].as_str().unwrap_or("(unnamed)");
+
+    let versions = previous_versions(item);
+    if to == 0 || to > versions.len() {
+        anyhow::bail!(
+            "Version {} does not exist; '{}' has {} previous version(s). Run 'bw-env history {}' to list them.",
+            to,
+            name,
+            versions.len(),
+            query
+        );
+    }
+    let target = &versions[to - 1];
+
+    let current = read_content(item)?;
+    let changes = diff_keys(&current, &target.content);
+    println!(
+        "\nRolling back '{}' to version {} ({})",
+        name, to, target.revision_date
+    );
+    println!("Changes: {}", changes.summary());
+    print!("\nProceed? [y/N]: ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .context("Failed to read user input")?;
+    if !matches!(input.trim(), "y" | "Y" | "yes") {
+        println!("Aborted, nothing was changed.");
+        return Ok(());
+    }
+
+    // The current content becomes a previous version, so a rollback can itself be undone
+    // (unless it is too large to keep in the history)
+    save_content(item, &target.content, layout_of(item), &encryption_of(item))?;
+
+    println!("Rolled back '{}' to the version from {}.", name, target.revision_date);
+    Ok(())
+}
+
+fn folder_items() -> Result<Vec<serde_json::Value>> {
+    let folder_id = find_folder_by_name(ROOT_FOLDER_NAME)?
+        .ok_or_else(|| anyhow!("No '{}' folder found in Bitwarden", ROOT_FOLDER_NAME))?;
+    let mut items = list_items(&folder_id)?;
+    items.retain(|item| !is_chunk(item));
+    Ok(items)
+}
@@ -1,12 +1,76 @@
 use crate::bw_commands::sync_vault;
+use crate::chunks::{is_chunk, sha256_hex};
+use crate::commands::history::{content_at_revision, previous_versions};
+use crate::commands::retrieve::{glob_match, repo_path};
+use crate::content::{attachment_file_name, inline_content};
+use crate::encryption::is_encrypted;
+use crate::naming::{build_tree, current_context_dir, item_file_name, DirNode};
+use crate::provenance::{
+    content_checksum, describe_provenance, matches_filters, provenance_keys, provenance_of,
+    validate_filters,
+};
+use crate::state::synced_revision;
+use crate::vault::{item_link, web_vault_url};
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use std::collections::HashMap;
+use std::fs;
 use std::process::Command;
 
 // Configuration: Root folder name in Bitwarden
 const ROOT_FOLDER_NAME: &str = "bw-env";
 
-pub fn list_env_items() -> Result<()> {
+/// Output formats for `list`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListFormat {
+    /// One decorated line per item, for reading
+    Text,
+    /// An array of objects, one per item
+    Json,
+    /// Tab-separated values with a header row; tabs, newlines and backslashes are escaped
+    Tsv,
+    /// Aligned columns
+    Table,
+}
+
+/// Orders for `list`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ListSort {
+    /// Alphabetically by name
+    Name,
+    /// Newest first
+    Created,
+    /// Most recently modified first
+    Modified,
+}
+
+/// What `list` shows and how
+pub struct ListOptions {
+    pub format: ListFormat,
+    /// Provenance filters (`KEY=VALUE`)
+    pub filters: Vec<String>,
+    /// Only items of this `owner/repo`; defaults to the current repository
+    pub repo: Option<String>,
+    /// List items of every repository, even inside one
+    pub all_repos: bool,
+    /// Glob matched against item names
+    pub name: Option<String>,
+    /// Only items modified on or after this date (`YYYY-MM-DD` or a full timestamp)
+    pub since: Option<String>,
+    /// Only items in this folder
+    pub folder: Option<String>,
+    pub sort: Option<ListSort>,
+    /// Nest items by owner, repository and directory
+    pub tree: bool,
+}
+
+pub fn list_env_items(options: &ListOptions) -> Result<()> {
+    let format = options.format;
+    validate_filters(&options.filters)?;
+    if let Some(since) = &options.since {
+        validate_since(since)?;
+    }
+
     // Sync with Bitwarden server before listing
     sync_vault()?;
 
@@ -15,17 +79,41 @@ pub fn list_env_items() -> Result<()> {
 
     if let Some(_id) = folder_id {
         // List all items in the bw-env folder hierarchy
-        list_items_in_folder()?;
+        list_items_in_folder(options)?;
     } else {
-        println!(
+        // Machine-readable output stays valid: the message goes to stderr
+        let message = format!(
             "No '{}' folder found in Bitwarden. Use 'bw-env store' to create it and add items.",
             ROOT_FOLDER_NAME
         );
+        match format {
+            ListFormat::Text => println!("{}", message),
+            _ => {
+                eprintln!("{}", message);
+                print_records(&[], format);
+            }
+        }
     }
 
     Ok(())
 }
 
+/// Fail unless `--since` starts with a `YYYY-MM-DD` date
+fn validate_since(since: &str) -> Result<()> {
+    let valid = since.len() >= 10
+        && since.chars().enumerate().take(10).all(|(idx, c)| {
+            if idx == 4 || idx == 7 {
+                c == '-'
+            } else {
+                c.is_ascii_digit()
+            }
+        });
+    if !valid {
+        anyhow::bail!("Invalid date '{}'; expected YYYY-MM-DD", since);
+    }
+    Ok(())
+}
+
 fn get_bw_env_folder_id() -> Result<Option<String>> {
     let folders_output = Command::new("bw")
         .args(["list", "folders"])
@@ -52,29 +140,54 @@ fn get_bw_env_folder_id() -> Result<Option<String>> {
     Ok(None)
 }
 
-fn list_items_in_folder() -> Result<()> {
+fn list_items_in_folder(options: &ListOptions) -> Result<()> {
+    let format = options.format;
+
     // First, get all folders to build a folder hierarchy map
     let folder_map = build_folder_hierarchy()?;
 
     // Get items from all nested folders within bw-env
-    let all_items = get_all_items_in_bw_env_hierarchy(&folder_map)?;
+    let mut all_items = get_all_items_in_bw_env_hierarchy(&folder_map)?;
+    let repo = filter_and_sort(&mut all_items, &folder_map, options);
+    let vault_url = web_vault_url();
+    let context = current_context_dir();
+
+    if format != ListFormat::Text {
+        let records: Vec<serde_json::Value> = all_items
+            .iter()
+            .map(|item| item_record(item, &folder_map, &vault_url, context.as_deref()))
+            .collect();
+        print_records(&records, format);
+        return Ok(());
+    }
+
+    // Only the current repository is listed by default, so say so
+    let scope = repo
+        .map(|repo| format!(" for {} (use --all-repos to list every repository)", repo))
+        .unwrap_or_default();
 
     if all_items.is_empty() {
         println!(
-            "No .env files found in the '{}' folder or its subfolders.",
-            ROOT_FOLDER_NAME
+            "No matching .env files found in the '{}' folder or its subfolders{}.",
+            ROOT_FOLDER_NAME, scope
         );
         return Ok(());
     }
 
     println!();
     println!(
-        "Found {} .env file(s) in 📁 '{}' folder:",
+        "Found {} .env file(s) in 📁 '{}' folder{}:",
         all_items.len(),
-        ROOT_FOLDER_NAME
+        ROOT_FOLDER_NAME,
+        scope
     );
     println!();
 
+    if options.tree {
+        print_tree(&all_items);
+        return Ok(());
+    }
+
     for item in all_items {
         if let (Some(name), Some(id)) = (item["name"].as_str(), item["id"].as_str()) {
             // Get the folder path and folder ID for this item
@@ -90,22 +203,350 @@ fn list_items_in_folder() -> Result<()> {
                 .map(format_date)
                 .unwrap_or_else(|| "Unknown".to_string());
 
-            // Generate Bitwarden vault link
-            let vault_link = format!(
-                "https://vault.bitwarden.com/#/vault?folderId={}&itemId={}&action=view",
-                folder_id, id
-            );
+            let vault_link = item_link(&vault_url, &folder_id, id);
+
+            // Client-side encrypted items are encrypted twice: by age and by the vault
+            let encryption = if is_encrypted(&item) {
+                " | 🔐 Doubly encrypted (age)"
+            } else {
+                ""
+            };
+
+            // Items of the current repository show how the local file compares
+            let drift = drift_of(&item, context.as_deref())
+                .map(|drift| format!(" | {}", drift.label()))
+                .unwrap_or_default();
 
             println!(
-                "📄 {} | Created: {} | Modified: {} | {}",
-                name, created, modified, vault_link
+                "📄 {} | Created: {} | Modified: {}{}{} | {}",
+                name, created, modified, encryption, drift, vault_link
             );
+            if let Some(provenance) = describe_provenance(&item) {
+                println!("   ↳ Stored {}", provenance);
+            }
         }
     }
 
     Ok(())
 }
 
+/// Apply the `list` filters and order to the fetched items, without further `bw` calls.
+///
+/// Returns the repository the items were restricted to, if any.
+fn filter_and_sort(
+    items: &mut Vec<serde_json::Value>,
+    folder_map: &HashMap<String, serde_json::Value>,
+    options: &ListOptions,
+) -> Option<String> {
+    let repo = if options.all_repos {
+        None
+    } else {
+        options.repo.clone().or_else(current_context_dir)
+    };
+    let repo = repo.map(|repo| repo.trim_end_matches('/').to_string());
+    let repo_prefix = repo.as_ref().map(|repo| format!("{}/", repo));
+
+    items.retain(|item| {
+        let name = item["name"].as_str().unwrap_or("");
+        let modified = item["revisionDate"].as_str().unwrap_or("");
+        matches_filters(item, &options.filters)
+            && repo_prefix
+                .as_deref()
+                .is_none_or(|prefix| name.starts_with(prefix))
+            && options
+                .name
+                .as_deref()
+                .is_none_or(|pattern| glob_match(pattern, name))
+            // ISO 8601 timestamps compare correctly as strings
+            && options
+                .since
+                .as_deref()
+                .is_none_or(|since| modified >= since)
+            && options
+                .folder
+                .as_deref()
+                .is_none_or(|folder| get_folder_info(item, folder_map).0 == folder)
+    });
+
+    let date = |item: &serde_json::Value, key: &str| item[key].as_str().unwrap_or("").to_string();
+    match options.sort {
+        Some(ListSort::Name) => {
+            items.sort_by_key(|item| item["name"].as_str().unwrap_or("").to_lowercase())
+        }
+        Some(ListSort::Created) => {
+            items.sort_by_key(|item| std::cmp::Reverse(date(item, "creationDate")))
+        }
+        Some(ListSort::Modified) => {
+            items.sort_by_key(|item| std::cmp::Reverse(date(item, "revisionDate")))
+        }
+        None => {}
+    }
+    repo
+}
+
+/// How the local file of an item compares with its vault copy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Drift {
+    /// No local file at the item's path
+    Missing,
+    /// Same content as the vault copy
+    Identical,
+    /// Local edits that are not in the vault yet
+    Modified,
+    /// The vault has a newer version than the local file
+    Behind,
+    /// Both the local file and the vault copy changed since the last sync
+    Diverged,
+    /// No content checksum to compare with, or the file differs and there is no sync record
+    /// to tell which side changed
+    Unknown,
+}
+
+impl Drift {
+    fn as_str(self) -> &'static str {
+        match self {
+            Drift::Missing => "missing",
+            Drift::Identical => "identical",
+            Drift::Modified => "modified",
+            Drift::Behind => "behind",
+            Drift::Diverged => "diverged",
+            Drift::Unknown => "unknown",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Drift::Missing => "❔ Missing locally",
+            Drift::Identical => "✅ Up to date",
+            Drift::Modified => "✏️ Modified locally",
+            Drift::Behind => "⬇️ Older than vault",
+            Drift::Diverged => "⚠️ Diverged (changed locally and in the vault)",
+            Drift::Unknown => "❓ Unknown (no checksum or sync record)",
+        }
+    }
+}
+
+/// Compare an item of the current repository (`context`) with its local file.
+///
+/// Hashes the content `retrieve` would write: notes and fields items are rebuilt from the
+/// listing, other layouts use the content checksum recorded on the item, so no item body is
+/// downloaded. When the contents differ, the revision the file was last synced with tells
+/// local edits, a newer vault copy and changes on both sides apart. Without a sync record the
+/// file is only known to be behind when it matches a version kept in the item's history.
+fn drift_of(item: &serde_json::Value, context: Option<&str>) -> Option<Drift> {
+    let name = item["name"].as_str()?;
+    name.strip_prefix(context?)?.strip_prefix('/')?;
+    let path = repo_path(name, attachment_file_name(item)).ok()??;
+
+    let Ok(bytes) = fs::read(&path) else {
+        return Some(Drift::Missing);
+    };
+    let checksum = match inline_content(item) {
+        Some(content) => sha256_hex(content.as_bytes()),
+        None => match content_checksum(item) {
+            Some(checksum) => checksum.to_string(),
+            None => return Some(Drift::Unknown),
+        },
+    };
+    let local_checksum = sha256_hex(&bytes);
+    if local_checksum == checksum {
+        return Some(Drift::Identical);
+    }
+
+    let revision = item["revisionDate"].as_str().unwrap_or("");
+    let synced = item["id"]
+        .as_str()
+        .and_then(|item_id| synced_revision(&path, item_id));
+    let holds_version = |version: &str| sha256_hex(version.as_bytes()) == local_checksum;
+    match synced {
+        Some(synced) if synced == revision => Some(Drift::Modified),
+        Some(synced) => {
+            // The vault moved on; the file is only behind if it still holds the synced
+            // version, which is known when that version is kept in the item's history
+            let unchanged = content_at_revision(item, &synced)
+                .ok()
+                .flatten()
+                .is_some_and(|content| holds_version(&content));
+            Some(if unchanged {
+                Drift::Behind
+            } else {
+                Drift::Diverged
+            })
+        }
+        None if previous_versions(item)
+            .iter()
+            .any(|version| holds_version(&version.content)) =>
+        {
+            Some(Drift::Behind)
+        }
+        None => Some(Drift::Unknown),
+    }
+}
+
+/// Print the items nested by owner, repository and directory, with item counts per directory
+fn print_tree(items: &[serde_json::Value]) {
+    for line in tree_lines(items) {
+        println!("{}", line);
+    }
+}
+
+/// Lines of the tree printed by `print_tree`
+fn tree_lines(items: &[serde_json::Value]) -> Vec<String> {
+    let mut lines = Vec::new();
+    tree_node_lines(items, &build_tree(items), "", &mut lines);
+    lines
+}
+
+fn tree_node_lines(
+    items: &[serde_json::Value],
+    node: &DirNode,
+    indent: &str,
+    lines: &mut Vec<String>,
+) {
+    let entries = node.items.len() + node.children.len();
+    let mut position = 0;
+    let mut branch = || {
+        position += 1;
+        if position == entries {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        }
+    };
+
+    for &idx in &node.items {
+        let (connector, _) = branch();
+        let name = item_file_name(items[idx]["name"].as_str().unwrap_or("(unnamed)"));
+        let encryption = if is_encrypted(&items[idx]) {
+            " 🔐"
+        } else {
+            ""
+        };
+        lines.push(format!("{}{}📄 {}{}", indent, connector, name, encryption));
+    }
+    for (segment, child) in &node.children {
+        let (connector, continuation) = branch();
+        lines.push(format!(
+            "{}{}📁 {} ({})",
+            indent,
+            connector,
+            segment,
+            child.count()
+        ));
+        tree_node_lines(items, child, &format!("{}{}", indent, continuation), lines);
+    }
+}
+
+/// Everything `list` reports about an item, for the machine-readable formats
+fn item_record(
+    item: &serde_json::Value,
+    folder_map: &HashMap<String, serde_json::Value>,
+    vault_url: &str,
+    context: Option<&str>,
+) -> serde_json::Value {
+    let (folder_path, folder_id) = get_folder_info(item, folder_map);
+    let id = item["id"].as_str().unwrap_or_default();
+    let provenance: serde_json::Map<String, serde_json::Value> = provenance_of(item)
+        .into_iter()
+        .map(|(key, value)| {
+            (
+                key.to_string(),
+                serde_json::Value::String(value.to_string()),
+            )
+        })
+        .collect();
+
+    serde_json::json!({
+        "id": id,
+        "name": item["name"],
+        "folder": folder_path,
+        "created": item["creationDate"],
+        "modified": item["revisionDate"],
+        "encrypted": is_encrypted(item),
+        "status": drift_of(item, context).map(Drift::as_str),
+        "link": item_link(vault_url, &folder_id, id),
+        "provenance": provenance,
+    })
+}
+
+fn print_records(records: &[serde_json::Value], format: ListFormat) {
+    let text = |value: &serde_json::Value| match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    let mut columns = vec![
+        "id",
+        "name",
+        "folder",
+        "created",
+        "modified",
+        "encrypted",
+        "status",
+        "link",
+    ];
+    columns.extend(provenance_keys());
+    let cells = |record: &serde_json::Value| -> Vec<String> {
+        columns
+            .iter()
+            .map(|column| match record.get(*column) {
+                Some(value) => text(value),
+                None => text(&record["provenance"][*column]),
+            })
+            .collect()
+    };
+
+    match format {
+        ListFormat::Json => {
+            let json = serde_json::to_string_pretty(records).unwrap_or_else(|_| "[]".to_string());
+            println!("{}", json);
+        }
+        ListFormat::Tsv => {
+            println!("{}", columns.join("\t"));
+            for record in records {
+                let escaped: Vec<String> =
+                    cells(record).iter().map(|cell| escape_tsv(cell)).collect();
+                println!("{}", escaped.join("\t"));
+            }
+        }
+        ListFormat::Table => {
+            let header: Vec<String> = columns.iter().map(|column| column.to_uppercase()).collect();
+            let mut rows = vec![header];
+            rows.extend(records.iter().map(|record| {
+                cells(record)
+                    .into_iter()
+                    .map(|cell| cell.replace(['\t', '\n', '\r'], " "))
+                    .collect()
+            }));
+
+            let widths: Vec<usize> = (0..columns.len())
+                .map(|column| {
+                    rows.iter()
+                        .map(|row| row[column].chars().count())
+                        .max()
+                        .unwrap_or(0)
+                })
+                .collect();
+            for row in rows {
+                let line: Vec<String> = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                    .collect();
+                println!("{}", line.join("  ").trim_end());
+            }
+        }
+        ListFormat::Text => {}
+    }
+}
+
+fn escape_tsv(cell: &str) -> String {
+    cell.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
 fn build_folder_hierarchy() -> Result<HashMap<String, serde_json::Value>> {
     let folders_output = Command::new("bw")
         .args(["list", "folders"])
@@ -148,7 +589,8 @@ fn get_all_items_in_bw_env_hierarchy(
             if let Ok(items) =
                 serde_json::from_slice::<Vec<serde_json::Value>>(&items_output.stdout)
             {
-                all_items.extend(items);
+                // Chunk items are listed through the item that links them
+                all_items.extend(items.into_iter().filter(|item| !is_chunk(item)));
             }
         }
     }
@@ -205,3 +647,164 @@ fn format_date(date_str: &str) -> String {
         date_str.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn options() -> ListOptions {
+        ListOptions {
+            format: ListFormat::Text,
+            filters: Vec::new(),
+            repo: None,
+            all_repos: true,
+            name: None,
+            since: None,
+            folder: None,
+            sort: None,
+            tree: false,
+        }
+    }
+
+    fn items() -> Vec<serde_json::Value> {
+        vec![
+            json!({
+                "name": "acme/web/.env",
+                "folderId": "root",
+                "creationDate": "2024-03-01T10:00:00.000Z",
+                "revisionDate": "2024-05-01T10:00:00.000Z",
+            }),
+            json!({
+                "name": "acme/API/.env",
+                "folderId": "nested",
+                "creationDate": "2024-01-01T10:00:00.000Z",
+                "revisionDate": "2024-06-01T00:00:00.000Z",
+            }),
+            json!({
+                "name": "other/app/.env",
+                "folderId": "root",
+                "creationDate": "2024-02-01T10:00:00.000Z",
+                "revisionDate": "2023-12-31T23:59:59.000Z",
+            }),
+        ]
+    }
+
+    fn listed(options: &ListOptions) -> Vec<String> {
+        let folder_map = HashMap::from([
+            ("root".to_string(), json!({ "name": "bw-env" })),
+            ("nested".to_string(), json!({ "name": "bw-env/work" })),
+        ]);
+        let mut items = items();
+        filter_and_sort(&mut items, &folder_map, options);
+        items
+            .iter()
+            .map(|item| item["name"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn sorts_by_name_case_insensitively_and_by_dates_newest_first() {
+        let sorted = |sort| {
+            listed(&ListOptions {
+                sort: Some(sort),
+                ..options()
+            })
+        };
+
+        assert_eq!(
+            sorted(ListSort::Name),
+            ["acme/API/.env", "acme/web/.env", "other/app/.env"]
+        );
+        assert_eq!(
+            sorted(ListSort::Created),
+            ["acme/web/.env", "other/app/.env", "acme/API/.env"]
+        );
+        assert_eq!(
+            sorted(ListSort::Modified),
+            ["acme/API/.env", "acme/web/.env", "other/app/.env"]
+        );
+        assert_eq!(
+            listed(&options()),
+            ["acme/web/.env", "acme/API/.env", "other/app/.env"]
+        );
+    }
+
+    #[test]
+    fn since_keeps_items_modified_on_or_after_the_cutoff() {
+        let since = |since: &str| {
+            listed(&ListOptions {
+                since: Some(since.to_string()),
+                ..options()
+            })
+        };
+
+        assert_eq!(since("2024-01-01"), ["acme/web/.env", "acme/API/.env"]);
+        assert_eq!(since("2024-06-01"), ["acme/API/.env"]);
+        assert_eq!(since("2024-06-01T00:00:01"), Vec::<String>::new());
+        assert_eq!(since("2023-12-31").len(), 3);
+    }
+
+    #[test]
+    fn since_must_start_with_a_date() {
+        assert!(validate_since("2024-01-01").is_ok());
+        assert!(validate_since("2024-01-01T12:00:00Z").is_ok());
+        assert!(validate_since("2024-1-01").is_err());
+        assert!(validate_since("01/02/2024").is_err());
+        assert!(validate_since("yesterday").is_err());
+    }
+
+    #[test]
+    fn filters_by_repository_name_and_folder() {
+        let repo = listed(&ListOptions {
+            repo: Some("acme/web/".to_string()),
+            all_repos: false,
+            ..options()
+        });
+        assert_eq!(repo, ["acme/web/.env"]);
+
+        let name = listed(&ListOptions {
+            name: Some("acme/*".to_string()),
+            ..options()
+        });
+        assert_eq!(name, ["acme/web/.env", "acme/API/.env"]);
+
+        let folder = listed(&ListOptions {
+            folder: Some("bw-env/work".to_string()),
+            ..options()
+        });
+        assert_eq!(folder, ["acme/API/.env"]);
+    }
+
+    #[test]
+    fn tree_nests_items_by_owner_repository_and_directory() {
+        let items: Vec<serde_json::Value> = [
+            "acme/api/.env",
+            "acme/api/config/.env.test",
+            "acme/web/.env",
+            "loose.env",
+        ]
+        .iter()
+        .map(|name| json!({ "name": name }))
+        .chain([json!({
+            "name": "acme/api/config/.env.prod",
+            "fields": [{ "name": "bw-env:encryption", "value": "passphrase" }],
+        })])
+        .collect();
+
+        assert_eq!(
+            tree_lines(&items),
+            [
+                "├── 📄 loose.env",
+                "└── 📁 acme (4)",
+                "    ├── 📁 api (3)",
+                "    │   ├── 📄 .env",
+                "    │   └── 📁 config (2)",
+                "    │       ├── 📄 .env.test",
+                "    │       └── 📄 .env.prod 🔐",
+                "    └── 📁 web (1)",
+                "        └── 📄 .env",
+            ]
+        );
+    }
+}
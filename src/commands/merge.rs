@@ -0,0 +1,148 @@
+use crate::bw_commands::{find_folder_by_name, list_items, sync_vault};
+use crate::commands::history::content_at_revision;
+use crate::commands::retrieve::find_item;
+use crate::commands::store::{find_item_for_file, save_content};
+use crate::content::{ensure_dotenv_layout, layout_of, read_content};
+use crate::dotenv::{diff_keys, three_way_merge, Conflict, Resolution};
+use crate::secure_file::write_secret_file;
+use crate::state::{record_sync, synced_revision};
+use crate::encryption::encryption_of;
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+const ROOT_FOLDER_NAME: &str = "bw-env";
+
+pub fn merge_env(path: &str, item: Option<&str>, markers: bool) -> Result<()> {
+    sync_vault()?;
+
+    let folder_id = find_folder_by_name(ROOT_FOLDER_NAME)?
+        .ok_or_else(|| anyhow!("No '{}' folder found in Bitwarden", ROOT_FOLDER_NAME))?;
+    let items = list_items(&folder_id)?;
+    let item = match item {
+        Some(name) => find_item(&items, name)?,
+        None => find_item_for_file(&items, path)?,
+    };
+
+    merge_file(path, item, markers, false)
+}
+
+/// Three-way merge the local file with the vault item, then write the result to both.
+///
+/// The base is the item revision the file was last synced with. Conflicts are resolved
+/// interactively, or written as conflict markers (leaving the vault untouched) when
+/// `markers` is set. With `confirm_push`, the user is asked before the vault is updated.
+pub(crate) fn merge_file(
+    path: &str,
+    item: &serde_json::Value,
+    markers: bool,
+    confirm_push: bool,
+) -> Result<()> {
+    let item_id = item["id"].as_str().ok_or_else(|| anyhow!("Missing item id"))?;
+    let name = item["name"].as_str().unwrap_or("(unnamed)");
+    ensure_dotenv_layout(item)?;
+
+    let local = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read .env file at {}", path))?;
+    let remote = read_content(item)?;
+
+    let base = match synced_revision(Path::new(path), item_id) {
+        Some(revision) => content_at_revision(item, &revision)?,
+        None => None,
+    };
+    if base.is_none() {
+        println!(
+            "No sync base recorded for {}; keys that differ on both sides are treated as conflicts.",
+            path
+        );
+    }
+
+    println!("\nMerging {} (local) with '{}' (vault)...", path, name);
+    let result = three_way_merge(base.as_deref().unwrap_or(""), &local, &remote, |conflict| {
+        if markers {
+            Resolution::Markers
+        } else {
+            prompt_conflict(conflict)
+        }
+    });
+
+    write_secret_file(Path::new(path), result.content.as_bytes())
+        .with_context(|| format!("Failed to write merged .env file to {}", path))?;
+
+    if !result.from_remote.is_empty() {
+        println!("Took from vault: {}", result.from_remote.join(", "));
+    }
+
+    if result.unresolved > 0 {
+        // The file now holds the vault's side of every key, so this revision is the new base
+        record_sync(Path::new(path), item);
+        println!(
+            "{} conflict(s) left as markers in {}. Resolve them, then run 'bw-env store {}'.",
+            result.unresolved, path, path
+        );
+        return Ok(());
+    }
+
+    if diff_keys(&remote, &result.content).is_empty() {
+        record_sync(Path::new(path), item);
+        println!("Vault copy already up to date.");
+    } else if confirm_push && !confirm(&format!("Push the merged result to '{}'?", name))? {
+        // The file holds the vault's changes, so only the local ones remain to be stored
+        record_sync(Path::new(path), item);
+        println!(
+            "Kept the merged result in {} only; run 'bw-env store {}' to push it.",
+            path, path
+        );
+        return Ok(());
+    } else {
+        let edited = save_content(item, &result.content, layout_of(item), &encryption_of(item))?;
+        record_sync(Path::new(path), &edited);
+    }
+
+    println!(
+        "Merged {} with '{}' ({} conflict(s) resolved).",
+        path, name, result.conflicts
+    );
+    Ok(())
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    print!("{} [y/N]: ", question);
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .context("Failed to read user input")?;
+    Ok(matches!(input.trim(), "y" | "Y" | "yes"))
+}
+
+fn prompt_conflict(conflict: &Conflict) -> Resolution {
+    let describe = |value: &Option<String>| match value {
+        Some(value) => format!("{}={}", conflict.key, value),
+        None => "(removed)".to_string(),
+    };
+
+    println!("\nConflict on {}:", conflict.key);
+    println!("1. Keep local: {}", describe(&conflict.local));
+    println!("2. Keep vault: {}", describe(&conflict.remote));
+    println!("3. Write conflict markers");
+
+    loop {
+        print!("Enter your choice: ");
+        io::stdout().flush().unwrap();
+
+        let mut input = String::new();
+        if io::stdin().read_line(&mut input).unwrap_or(0) == 0 {
+            // No more input available; leave the conflict for manual resolution
+            return Resolution::Markers;
+        }
+        match input.trim() {
+            "1" => return Resolution::Local,
+            "2" => return Resolution::Remote,
+            "3" => return Resolution::Markers,
+            _ => println!("Invalid choice, please enter 1, 2 or 3."),
+        }
+    }
+}
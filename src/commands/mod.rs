@@ -1,7 +1,23 @@
 pub mod store;
 pub mod retrieve;
 pub mod list;
+pub mod history;
+pub mod diff;
+pub mod direnv;
+pub mod export;
+pub mod merge;
+pub mod open;
+pub mod run;
+pub mod session;
 
 pub use store::store_env;
-pub use retrieve::retrieve_env;
-pub use list::list_env_items;
\ No newline at end of file
+pub use retrieve::{retrieve_env, Overwrite};
+pub use list::list_env_items;
+pub use history::{history_env, rollback_env};
+pub use diff::diff_env;
+pub use direnv::direnv_env;
+pub use export::export_env;
+pub use merge::merge_env;
+pub use open::open_env;
+pub use run::run_env;
+pub use session::{lock_env, unlock_env};
\ No newline at end of file
@@ -0,0 +1,51 @@
+use crate::bw_commands::{find_folder_by_name, list_items, sync_vault};
+use crate::chunks::is_chunk;
+use crate::commands::run::resolve_item;
+use crate::vault::{item_link, web_vault_url};
+use anyhow::{anyhow, Context, Result};
+use std::process::Command;
+
+const ROOT_FOLDER_NAME: &str = "bw-env";
+
+/// Open a stored item in the web vault of the configured server
+pub fn open_env(item: Option<&str>) -> Result<()> {
+    sync_vault()?;
+    let folder_id = find_folder_by_name(ROOT_FOLDER_NAME)?
+        .ok_or_else(|| anyhow!("No '{}' folder found in Bitwarden", ROOT_FOLDER_NAME))?;
+    let mut items = list_items(&folder_id)?;
+    items.retain(|item| !is_chunk(item));
+
+    let item = resolve_item(&items, item)?;
+    let item_id = item["id"].as_str().ok_or_else(|| anyhow!("Missing item id"))?;
+    let item_folder_id = item["folderId"].as_str().unwrap_or(&folder_id);
+    let link = item_link(&web_vault_url(), item_folder_id, item_id);
+
+    println!(
+        "🌐 Opening '{}' in the web vault: {}",
+        item["name"].as_str().unwrap_or("(unnamed)"),
+        link
+    );
+    launch_browser(&link)
+}
+
+fn launch_browser(url: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        // Not `cmd /C start`: cmd.exe would treat the `&` in the link as a command separator
+        let mut command = Command::new("rundll32");
+        command.arg("url.dll,FileProtocolHandler");
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+
+    let status = command
+        .arg(url)
+        .status()
+        .context("Failed to launch the browser; open the link above manually")?;
+    if !status.success() {
+        anyhow::bail!("Failed to launch the browser; open the link above manually");
+    }
+    Ok(())
+}
@@ -1,28 +1,152 @@
 use anyhow::{anyhow, Context, Result};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 use std::fs;
-use std::io::{self, Write};
-use std::path::PathBuf;
-use std::process::Command;
-use crate::bw_commands::{find_folder_by_name, sync_vault};
+use std::io::{self, IsTerminal, Write};
+use std::path::{Component, Path, PathBuf};
+use crate::bw_commands::{find_folder_by_name, get_attachment, get_item, list_items, sync_vault};
+use crate::chunks::is_chunk;
+use crate::commands::diff::print_changes;
+use crate::commands::merge::merge_file;
+use crate::git::{git_output, parent_dir, repo_root};
+use crate::naming::{current_context_dir, group_items_by_dir, item_dir, item_file_name};
+use crate::content::{attachment_file_name, layout_of, read_content, Layout};
+use crate::encryption::is_encrypted;
+use crate::picker::{is_interactive, pick, Entry};
+use crate::provenance::{describe_provenance, matches_filters, provenance_of, validate_filters};
+use crate::secure_file::write_secret_file;
+use crate::state::record_sync;
 
 const ROOT_FOLDER_NAME: &str = "bw-env";
 
-pub fn retrieve_env() -> Result<()> {
+/// What to do when a downloaded file would replace a different local file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overwrite {
+    /// Show the differences and ask
+    Ask,
+    /// Overwrite without asking
+    Force,
+    /// Keep a timestamped backup of the local file, then overwrite it
+    Backup,
+    /// Keep the local file
+    Skip,
+    /// Fail, as nobody can be asked
+    Refuse,
+}
+
+/// How and where items are written locally
+#[derive(Clone, Copy)]
+struct DownloadOptions<'a> {
+    output: Option<&'a Path>,
+    flat: bool,
+    overwrite: Overwrite,
+}
+
+pub fn retrieve_env(
+    query: Option<&str>,
+    output: Option<&str>,
+    flat: bool,
+    overwrite: Overwrite,
+    filters: &[String],
+) -> Result<()> {
+    validate_filters(filters)?;
     sync_vault()?;
 
     let folder_id = find_folder_by_name(ROOT_FOLDER_NAME)?
         .ok_or_else(|| anyhow!("No '{}' folder found in Bitwarden", ROOT_FOLDER_NAME))?;
 
-    let items = list_items(&folder_id)?;
+    let mut items = list_items(&folder_id)?;
+    // Chunk items are reassembled through the item that links them
+    items.retain(|item| !is_chunk(item) && matches_filters(item, filters));
     if items.is_empty() {
-        println!("No .env items found in '{}' folder.", ROOT_FOLDER_NAME);
+        // Scripts asking for a specific item must not mistake "nothing found" for success
+        if let Some(query) = query {
+            anyhow::bail!(
+                "No item matches '{}' in the '{}' folder{}",
+                query,
+                ROOT_FOLDER_NAME,
+                if filters.is_empty() { "" } else { " with the given --where filters" }
+            );
+        }
+        println!("No matching .env items found in '{}' folder.", ROOT_FOLDER_NAME);
         return Ok(());
     }
 
+    // A named item is downloaded directly, without prompting
+    if let Some(query) = query {
+        let item = find_item(&items, query)?;
+        // Without a terminal there is nobody to ask about a local file that differs
+        let overwrite = if overwrite == Overwrite::Ask && !io::stdin().is_terminal() {
+            Overwrite::Refuse
+        } else {
+            overwrite
+        };
+        let options = DownloadOptions {
+            output: output.map(Path::new),
+            flat,
+            overwrite,
+        };
+        report_download(item, download_item(item, options)?);
+        return Ok(());
+    }
+
+    // Full-screen fuzzy finder on a terminal, numbered menu otherwise (e.g. when piped)
+    let selected = if is_interactive() {
+        match pick_items(&items)? {
+            Some(selected) => selected,
+            None => {
+                println!("Nothing selected.");
+                return Ok(());
+            }
+        }
+    } else {
+        prompt_selection(&items)?
+    };
+    let download_options = DownloadOptions {
+        output: None,
+        flat,
+        overwrite,
+    };
+
+    println!("Stored folder: '{}'", ROOT_FOLDER_NAME);
+    // Download everything selected, reporting failures at the end instead of stopping early
+    let results: Vec<(&serde_json::Value, Result<Option<PathBuf>>)> = selected
+        .iter()
+        .map(|&idx| (&items[idx], download_item(&items[idx], download_options)))
+        .collect();
+
+    if results.len() > 1 {
+        println!("\nSummary:");
+    }
+    let mut failed = 0;
+    for (item, result) in results {
+        match result {
+            Ok(path) => report_download(item, path),
+            Err(err) => {
+                failed += 1;
+                println!(
+                    "Failed item: '{}': {:#}",
+                    item["name"].as_str().unwrap_or("env"),
+                    err
+                );
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} item(s) failed to download",
+            failed,
+            selected.len()
+        );
+    }
+    Ok(())
+}
+
+/// Numbered menu of the items, grouped by directory with the current repository first;
+/// returns the indices of the picked items
+fn prompt_selection(items: &[serde_json::Value]) -> Result<Vec<usize>> {
     // Try to infer context (org/repo) from current git remote
     let context_dir = current_context_dir();
-    let groups = group_items_by_dir(&items);
+    let groups = group_items_by_dir(items);
 
     let mut options: Vec<OptionEntry> = Vec::new();
     let mut next_num: usize = 1;
@@ -38,7 +162,7 @@ pub fn retrieve_env() -> Result<()> {
                 let segments: Vec<String> = context_indices
                     .iter()
                     .filter_map(|&idx| items[idx]["name"].as_str())
-                    .map(|name| name.rsplit(['/', '\\']).next().unwrap_or(name).to_string())
+                    .map(|name| item_file_name(name).to_string())
                     .filter(|seg| seen.insert(seg.clone()))
                     .collect();
 
@@ -53,31 +177,31 @@ pub fn retrieve_env() -> Result<()> {
 
             println!("\nDownload items individually:\n");
             for &idx in context_indices {
-                let name = items[idx]["name"].as_str().unwrap_or("(unnamed)");
+                let label = item_label(&items[idx]);
+                println!("{}. {}", next_num, label);
                 options.push(OptionEntry {
-                    label: name.to_string(),
+                    label,
                     kind: SelectionKind::Single(idx),
                 });
-                println!("{}. {}", next_num, name);
                 next_num += 1;
             }
 
             println!("\nOther items found on your vault:\n");
             for (idx, item) in items.iter().enumerate() {
                 if !context_set.contains(&idx) {
-                    let name = item["name"].as_str().unwrap_or("(unnamed)");
+                    let label = item_label(item);
+                    println!("{}. {}", next_num, label);
                     options.push(OptionEntry {
-                        label: name.to_string(),
+                        label,
                         kind: SelectionKind::Single(idx),
                     });
-                    println!("{}. {}", next_num, name);
                     next_num += 1;
                 }
             }
         } else {
             // No matches for context; fallback to grouped listing
-            let fallback = build_options(&items);
-            println!("Select what to download to the current directory:\n");
+            let fallback = build_options(items);
+            println!("Select what to download:\n");
             options = fallback;
             for (idx, opt) in options.iter().enumerate() {
                 println!("{}. {}", idx + 1, opt.label);
@@ -85,15 +209,15 @@ pub fn retrieve_env() -> Result<()> {
         }
     } else {
         // No context available; fallback to grouped listing
-        let fallback = build_options(&items);
-        println!("Select what to download to the current directory:\n");
+        let fallback = build_options(items);
+        println!("Select what to download:\n");
         options = fallback;
         for (idx, opt) in options.iter().enumerate() {
             println!("{}. {}", idx + 1, opt.label);
         }
     }
 
-    print!("\nEnter your choice: ");
+    print!("\nEnter your choice (e.g. 2, 1,3-5, all or a name glob): ");
     io::stdout().flush().unwrap();
 
     let mut input = String::new();
@@ -101,35 +225,221 @@ pub fn retrieve_env() -> Result<()> {
         .read_line(&mut input)
         .context("Failed to read selection")?;
 
-    let choice: usize = input
-        .trim()
-        .parse()
-        .context("Invalid choice, please enter a number")?;
+    parse_selection(input.trim(), &options, items)
+}
+
+/// Fuzzy finder over the items, with the current repository's items listed first
+fn pick_items(items: &[serde_json::Value]) -> Result<Option<Vec<usize>>> {
+    let context_dir = current_context_dir();
+    let in_context = |item: &serde_json::Value| {
+        let name = item["name"].as_str().unwrap_or("");
+        context_dir
+            .as_deref()
+            .is_some_and(|dir| name.starts_with(&format!("{}/", dir)))
+    };
 
-    if choice == 0 || choice > options.len() {
-        anyhow::bail!("Selection out of range");
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by_key(|&idx| !in_context(&items[idx]));
+
+    let entries: Vec<Entry> = order
+        .iter()
+        .map(|&idx| Entry {
+            group: match &context_dir {
+                Some(dir) if in_context(&items[idx]) => format!("📁 {} (current repository)", dir),
+                _ => "Other items".to_string(),
+            },
+            label: items[idx]["name"].as_str().unwrap_or("(unnamed)").to_string(),
+            preview: item_preview(&items[idx]),
+        })
+        .collect();
+
+    let picked = pick("Download", &entries, true)?;
+    Ok(picked.map(|picked| picked.into_iter().map(|entry| order[entry]).collect()))
+}
+
+/// Metadata lines describing an item, for the picker's preview pane
+pub(crate) fn item_preview(item: &serde_json::Value) -> Vec<String> {
+    let field = |key: &str| item[key].as_str().unwrap_or("unknown").to_string();
+    let mut lines = vec![
+        field("name"),
+        String::new(),
+        format!("id:       {}", field("id")),
+        format!("created:  {}", field("creationDate")),
+        format!("modified: {}", field("revisionDate")),
+        format!("layout:   {}", layout_of(item).as_str()),
+    ];
+    if is_encrypted(item) {
+        lines.push("🔐 encrypted with age".to_string());
     }
-    let selected = &options[choice - 1];
 
-    match &selected.kind {
-        SelectionKind::Single(idx) => {
-            let item = &items[*idx];
-            let path = download_item(item)?;
-            let raw_name = item["name"].as_str().unwrap_or("env");
-            println!("Stored folder: '{}'", ROOT_FOLDER_NAME);
-            println!("Downloaded item: '{}' -> {:?}", raw_name, path);
+    let provenance = provenance_of(item);
+    if !provenance.is_empty() {
+        lines.push(String::new());
+        lines.extend(
+            provenance
+                .into_iter()
+                .map(|(key, value)| format!("{}: {}", key, value)),
+        );
+    }
+    lines
+}
+
+/// Item indices picked at the prompt: menu numbers and ranges separated by commas
+/// (`1,3-5`), `all`, or a glob matched against item names
+fn parse_selection(
+    input: &str,
+    options: &[OptionEntry],
+    items: &[serde_json::Value],
+) -> Result<Vec<usize>> {
+    if input.eq_ignore_ascii_case("all") {
+        return Ok((0..items.len()).collect());
+    }
+
+    let option_items = |number: usize| -> Result<Vec<usize>> {
+        let option = number
+            .checked_sub(1)
+            .and_then(|idx| options.get(idx))
+            .ok_or_else(|| anyhow!("Selection {} is out of range", number))?;
+        Ok(match &option.kind {
+            SelectionKind::Single(idx) => vec![*idx],
+            SelectionKind::Group(indices) => indices.clone(),
+        })
+    };
+
+    let mut selected = Vec::new();
+    for part in input
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let numbers = match part.split_once('-') {
+            Some((start, end)) => start
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .zip(end.trim().parse().ok()),
+            None => part.parse::<usize>().ok().map(|number| (number, number)),
+        };
+        match numbers {
+            Some((start, end)) if start <= end => {
+                for number in start..=end {
+                    selected.extend(option_items(number)?);
+                }
+            }
+            Some(_) => anyhow::bail!("Invalid range '{}'", part),
+            None => {
+                let matches: Vec<usize> = items
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, item)| {
+                        item["name"]
+                            .as_str()
+                            .is_some_and(|name| glob_match(part, name))
+                    })
+                    .map(|(idx, _)| idx)
+                    .collect();
+                if matches.is_empty() {
+                    anyhow::bail!("No item matches '{}'", part);
+                }
+                selected.extend(matches);
+            }
+        }
+    }
+
+    // Items picked more than once are downloaded once, in the order first picked
+    let mut seen = HashSet::new();
+    selected.retain(|idx| seen.insert(*idx));
+    if selected.is_empty() {
+        anyhow::bail!("Nothing selected");
+    }
+    Ok(selected)
+}
+
+fn report_download(item: &serde_json::Value, path: Option<PathBuf>) {
+    let raw_name = item["name"].as_str().unwrap_or("env");
+    match path {
+        Some(path) => println!("Downloaded item: '{}' -> {:?}", raw_name, path),
+        None => println!("Skipped item: '{}'", raw_name),
+    }
+}
+
+/// Find a single item by exact id, name or name glob (`*` and `?`), failing with the
+/// candidates when ambiguous
+pub(crate) fn find_item<'a>(
+    items: &'a [serde_json::Value],
+    query: &str,
+) -> Result<&'a serde_json::Value> {
+    if let Some(item) = items.iter().find(|item| item["id"].as_str() == Some(query)) {
+        return Ok(item);
+    }
+
+    let mut matches: Vec<&serde_json::Value> = items
+        .iter()
+        .filter(|item| item["name"].as_str() == Some(query))
+        .collect();
+    if matches.is_empty() && query.contains(['*', '?']) {
+        matches = items
+            .iter()
+            .filter(|item| item["name"].as_str().is_some_and(|name| glob_match(query, name)))
+            .collect();
+    }
+
+    match matches.as_slice() {
+        [] => anyhow::bail!("No item named '{}' found in '{}' folder", query, ROOT_FOLDER_NAME),
+        [item] => Ok(item),
+        _ => {
+            let candidates: Vec<String> = matches
+                .iter()
+                .map(|item| {
+                    format!(
+                        "  - {} (id: {}, modified: {})",
+                        item["name"].as_str().unwrap_or("(unnamed)"),
+                        item["id"].as_str().unwrap_or("?"),
+                        item["revisionDate"].as_str().unwrap_or("unknown")
+                    )
+                })
+                .collect();
+            anyhow::bail!(
+                "'{}' matches {} items; use an item id instead:\n{}",
+                query,
+                matches.len(),
+                candidates.join("\n")
+            )
         }
-        SelectionKind::Group(indices) => {
-            println!("Stored folder: '{}'", ROOT_FOLDER_NAME);
-            for &idx in indices {
-                let item = &items[idx];
-                let path = download_item(item)?;
-                let raw_name = item["name"].as_str().unwrap_or("env");
-                println!("Downloaded item: '{}' -> {:?}", raw_name, path);
+    }
+}
+
+/// Match a name against a glob pattern where `*` matches any run of characters and `?`
+/// matches exactly one
+pub(crate) fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name position it was tried against
+    let mut backtrack: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
             }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` swallow one more character and retry
+                Some((star, matched)) => {
+                    backtrack = Some((star, matched + 1));
+                    p = star + 1;
+                    n = matched + 1;
+                }
+                None => return false,
+            },
         }
     }
-    Ok(())
+    pattern[p..].iter().all(|&c| c == '*')
 }
 
 struct OptionEntry {
@@ -150,7 +460,7 @@ fn build_options(items: &[serde_json::Value]) -> Vec<OptionEntry> {
     let grouped_dirs_order: Vec<String> = items
         .iter()
         .filter_map(|item| item["name"].as_str().and_then(item_dir))
-        .filter(|dir| groups.get(dir).map_or(false, |v| v.len() > 1))
+        .filter(|dir| groups.get(dir).is_some_and(|v| v.len() > 1))
         .filter(|dir| seen.insert(dir.clone()))
         .collect();
 
@@ -171,12 +481,9 @@ fn build_options(items: &[serde_json::Value]) -> Vec<OptionEntry> {
             label: format!("{}/* ({} items)", dir, indices.len()),
             kind: SelectionKind::Group(indices.clone()),
         });
-        entries.extend(indices.into_iter().map(|idx| {
-            let name = items[idx]["name"].as_str().unwrap_or("(unnamed)");
-            OptionEntry {
-                label: format!("  - {}", name),
-                kind: SelectionKind::Single(idx),
-            }
+        entries.extend(indices.into_iter().map(|idx| OptionEntry {
+            label: format!("  - {}", item_label(&items[idx])),
+            kind: SelectionKind::Single(idx),
         }));
         entries
     }));
@@ -188,7 +495,7 @@ fn build_options(items: &[serde_json::Value]) -> Vec<OptionEntry> {
             .enumerate()
             .filter(|(idx, _)| !grouped_indices.contains(idx))
             .map(|(idx, item)| OptionEntry {
-                label: item["name"].as_str().unwrap_or("(unnamed)").to_string(),
+                label: item_label(item),
                 kind: SelectionKind::Single(idx),
             }),
     );
@@ -196,106 +503,343 @@ fn build_options(items: &[serde_json::Value]) -> Vec<OptionEntry> {
     options
 }
 
-fn item_dir(name: &str) -> Option<String> {
-    name.rfind('/')
-        .map(|pos| name[..pos].to_string())
-        .filter(|dir| !dir.is_empty())
+/// Menu label for a single item: its name, followed by where it was stored from when known
+fn item_label(item: &serde_json::Value) -> String {
+    let name = item["name"].as_str().unwrap_or("(unnamed)");
+    match describe_provenance(item) {
+        Some(provenance) => format!("{} ({})", name, provenance),
+        None => name.to_string(),
+    }
+}
+
+/// Download an item to `output`, or to its original path in the repository (just its file
+/// name in the current directory when `flat`).
+///
+/// Returns where the item was written, or `None` when an existing local file was kept.
+fn download_item(item: &serde_json::Value, options: DownloadOptions) -> Result<Option<PathBuf>> {
+    let json = fetch_item(item)?;
+    if layout_of(&json) == Layout::Attachment {
+        return download_attachment(&json, options);
+    }
+
+    let raw_name = item["name"].as_str().unwrap_or("env");
+    let output_path = match options.output {
+        Some(output) => output.to_path_buf(),
+        None => target_path(raw_name, None, options.flat)?,
+    };
+
+    let content = read_content(&json)?;
+    match check_existing(&output_path, content.as_bytes(), options.overwrite, true)? {
+        ExistingFile::Write => {}
+        ExistingFile::Skip => return Ok(None),
+        ExistingFile::Merge => {
+            // Retrieving only writes locally unless the user agrees to push the merge
+            merge_file(&output_path.to_string_lossy(), &json, false, true)?;
+            return Ok(Some(output_path));
+        }
+    }
+
+    write_secret_file(&output_path, content.as_bytes())
+        .with_context(|| format!("Failed to write .env file to {:?}", output_path))?;
+    record_sync(&output_path, &json);
+
+    Ok(Some(output_path))
 }
 
-fn download_item(item: &serde_json::Value) -> Result<PathBuf> {
+/// Download an item's attachment byte-for-byte to `output`, or under its original file name
+fn download_attachment(
+    item: &serde_json::Value,
+    options: DownloadOptions,
+) -> Result<Option<PathBuf>> {
     let item_id = item["id"].as_str().ok_or_else(|| anyhow!("Missing item id"))?;
     let raw_name = item["name"].as_str().unwrap_or("env");
-    let file_name = sanitize_filename(raw_name);
-    let output_path = PathBuf::from(file_name.clone());
+    let attachments = item["attachments"].as_array().cloned().unwrap_or_default();
 
-    let output_json = Command::new("bw")
-        .args(["get", "item", item_id])
-        .output()
-        .context("Failed to execute Bitwarden CLI")?;
+    let original_name = attachment_file_name(item);
+    let attachment = attachments
+        .iter()
+        .find(|attachment| attachment["fileName"].as_str() == original_name)
+        .or_else(|| attachments.first())
+        .ok_or_else(|| anyhow!("Item '{}' has no attachment to download", raw_name))?;
+    let attachment_id = attachment["id"]
+        .as_str()
+        .ok_or_else(|| anyhow!("Missing attachment id"))?;
+
+    let file_name = original_name.or_else(|| attachment["fileName"].as_str());
+    let output_path = match options.output {
+        Some(output) => output.to_path_buf(),
+        None => target_path(raw_name, file_name, options.flat)?,
+    };
 
-    if !output_json.status.success() {
-        anyhow::bail!("Bitwarden CLI failed to retrieve item '{}'.", raw_name);
+    let bytes = get_attachment(item_id, attachment_id)?;
+    // Arbitrary files cannot be merged key by key
+    if check_existing(&output_path, &bytes, options.overwrite, false)? != ExistingFile::Write {
+        return Ok(None);
     }
+    write_secret_file(&output_path, &bytes)
+        .with_context(|| format!("Failed to write attachment to {:?}", output_path))?;
+    record_sync(&output_path, item);
+
+    Ok(Some(output_path))
+}
 
-    let json: serde_json::Value = serde_json::from_slice(&output_json.stdout)
-        .context("Failed to parse Bitwarden item JSON")?;
+#[derive(PartialEq)]
+enum ExistingFile {
+    Write,
+    Skip,
+    Merge,
+}
 
-    let notes = json["notes"].as_str().unwrap_or("");
-    fs::write(&output_path, notes)
-        .with_context(|| format!("Failed to write .env file to {:?}", output_path))?;
+/// Decide what to do when `path` already exists with content other than `new`, backing
+/// it up first when asked to. Merging is only offered for dotenv files.
+fn check_existing(
+    path: &Path,
+    new: &[u8],
+    overwrite: Overwrite,
+    mergeable: bool,
+) -> Result<ExistingFile> {
+    let Ok(existing) = fs::read(path) else {
+        return Ok(ExistingFile::Write);
+    };
+    if existing == new {
+        return Ok(ExistingFile::Write);
+    }
 
-    Ok(output_path)
+    match overwrite {
+        Overwrite::Force => return Ok(ExistingFile::Write),
+        Overwrite::Backup => {
+            backup_file(path)?;
+            return Ok(ExistingFile::Write);
+        }
+        Overwrite::Skip => return Ok(ExistingFile::Skip),
+        Overwrite::Refuse => anyhow::bail!(
+            "{:?} already exists and differs from the vault copy; pass --force, --backup or --skip to decide without a prompt",
+            path
+        ),
+        Overwrite::Ask => {}
+    }
+
+    println!(
+        "\n⚠️  {:?} already exists and differs from the vault copy:\n",
+        path
+    );
+    if mergeable {
+        let local = String::from_utf8_lossy(&existing);
+        let remote = String::from_utf8_lossy(new);
+        if !print_changes(&local, &remote, false) {
+            println!("Only comments or formatting differ.");
+        }
+    }
+
+    let mut choices = vec![
+        (ExistingChoice::Overwrite, "Overwrite the local file"),
+        (ExistingChoice::Skip, "Skip (keep the local file) (default)"),
+    ];
+    if mergeable {
+        choices.push((ExistingChoice::Merge, "Merge local and vault changes"));
+    }
+    choices.push((
+        ExistingChoice::Backup,
+        "Back up the local file, then overwrite it",
+    ));
+
+    println!();
+    for (idx, (_, label)) in choices.iter().enumerate() {
+        println!("{}. {}", idx + 1, label);
+    }
+    print!("\nEnter your choice: ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .context("Failed to read user input")?;
+
+    let choice = match input.trim() {
+        "" => ExistingChoice::Skip,
+        other => other
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| number.checked_sub(1))
+            .and_then(|idx| choices.get(idx))
+            .map(|(choice, _)| *choice)
+            .ok_or_else(|| anyhow!("Invalid choice"))?,
+    };
+    match choice {
+        ExistingChoice::Overwrite => Ok(ExistingFile::Write),
+        ExistingChoice::Skip => Ok(ExistingFile::Skip),
+        ExistingChoice::Merge => Ok(ExistingFile::Merge),
+        ExistingChoice::Backup => {
+            backup_file(path)?;
+            Ok(ExistingFile::Write)
+        }
+    }
 }
 
-fn group_items_by_dir(items: &[serde_json::Value]) -> HashMap<String, Vec<usize>> {
-    items
-        .iter()
-        .enumerate()
-        .fold(HashMap::new(), |mut acc, (idx, item)| {
-            if let Some(dir) = item["name"].as_str().and_then(item_dir) {
-                acc.entry(dir).or_default().push(idx);
+#[derive(Clone, Copy)]
+enum ExistingChoice {
+    Overwrite,
+    Skip,
+    Merge,
+    Backup,
+}
+
+/// Copy the file to `<path>.<YYYYMMDD-HHMMSS>.bak` next to it, readable only by the user.
+///
+/// A backup taken in the same second gets a `-N` suffix instead of being replaced, and a
+/// warning is printed when git would not ignore the backup.
+fn backup_file(path: &Path) -> Result<()> {
+    let timestamp = backup_timestamp();
+    let backup_path = (0..)
+        .map(|attempt| {
+            let mut backup_name = path.file_name().unwrap_or_default().to_os_string();
+            match attempt {
+                0 => backup_name.push(format!(".{}.bak", timestamp)),
+                _ => backup_name.push(format!(".{}-{}.bak", timestamp, attempt)),
             }
-            acc
+            path.with_file_name(backup_name)
         })
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_default();
+
+    let content = fs::read(path).with_context(|| format!("Failed to read {:?}", path))?;
+    write_secret_file(&backup_path, &content)
+        .with_context(|| format!("Failed to back up {:?} to {:?}", path, backup_path))?;
+    println!("Backed up {:?} to {:?}", path, backup_path);
+
+    // The backup holds the same secrets as the file, but may not match its ignore rule
+    // (git runs in the backup's directory, so it is given the bare file name)
+    let dir = parent_dir(&backup_path);
+    let backup_name = backup_path.file_name().unwrap_or_default().to_string_lossy();
+    let ignored = git_output(dir, &["check-ignore", "--", &backup_name]).is_some();
+    if repo_root(dir).is_some() && !ignored {
+        println!(
+            "⚠️  {:?} is not ignored by git; add '*.bak' to .gitignore so it is never committed.",
+            backup_path
+        );
+    }
+    Ok(())
 }
 
-fn current_context_dir() -> Option<String> {
-    // Infer org/repo from git remote
-    let output = Command::new("git")
-        .args(["remote", "get-url", "origin"])
-        .output()
-        .ok()?;
-    if !output.status.success() {
-        return None;
-    }
-    let remote_url = String::from_utf8(output.stdout).ok()?;
-    let remote_url = remote_url.trim();
-
-    let repo_info = if remote_url.starts_with("https://github.com/") {
-        remote_url
-            .strip_prefix("https://github.com/")?
-            .strip_suffix(".git")
-            .unwrap_or(remote_url.strip_prefix("https://github.com/")?)
-            .to_string()
-    } else if remote_url.starts_with("git@github.com:") {
-        remote_url
-            .strip_prefix("git@github.com:")?
-            .strip_suffix(".git")
-            .unwrap_or(remote_url.strip_prefix("git@github.com:")?)
-            .to_string()
-    } else {
-        return None;
-    };
+/// Current UTC time as `YYYYMMDD-HHMMSS`
+fn backup_timestamp() -> String {
+    let (year, month, day, hour, minute, second) = utc_parts(std::time::SystemTime::now());
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year, month, day, hour, minute, second
+    )
+}
 
-    let parts: Vec<&str> = repo_info.split('/').collect();
-    if parts.len() == 2 {
-        Some(format!("{}/{}", parts[0], parts[1]))
-    } else {
-        None
+/// UTC year, month, day, hour, minute and second of a point in time
+fn utc_parts(time: std::time::SystemTime) -> (i64, i64, i64, i64, i64, i64) {
+    let secs = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or(0);
+    let (days, time) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    (year, month, day, time / 3_600, time % 3_600 / 60, time % 60)
+}
+
+/// Where to write an item when no output path was given, creating missing directories.
+///
+/// Only items of the current repository go back to their path in it; other items are
+/// written under their file name in the current directory.
+fn target_path(item_name: &str, file_name: Option<&str>, flat: bool) -> Result<PathBuf> {
+    let flat_path = PathBuf::from(sanitize_filename(file_name.unwrap_or(item_name)));
+    if flat {
+        return Ok(flat_path);
+    }
+
+    let Some(path) = repo_path(item_name, file_name)? else {
+        if current_context_dir().is_some() {
+            println!(
+                "'{}' is not from this repository; writing it to {:?} (pick another path with --output)",
+                item_name, flat_path
+            );
+        }
+        return Ok(flat_path);
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {:?}", parent))?;
     }
+    Ok(path)
 }
 
-fn list_items(folder_id: &str) -> Result<Vec<serde_json::Value>> {
-    let items_output = Command::new("bw")
-        .args(["list", "items", "--folderid", folder_id])
-        .output()
-        .context("Failed to list items in Bitwarden folder")?;
+/// Local path of an item of the current repository, or `None` for items of other
+/// repositories and outside a repository.
+///
+/// Paths escaping the repository are refused.
+pub(crate) fn repo_path(item_name: &str, file_name: Option<&str>) -> Result<Option<PathBuf>> {
+    let Some(context) = current_context_dir() else {
+        return Ok(None);
+    };
+    let Some(relative) = path_in_repo(item_name, &context, file_name)? else {
+        return Ok(None);
+    };
+    let root = repo_root(Path::new(".")).unwrap_or_else(|| PathBuf::from("."));
+    Ok(Some(root.join(relative)))
+}
+
+/// Path relative to the repository root of an item of the `context` repository.
+///
+/// The item name is mapped back by dropping its `owner/repo/` prefix; `file_name` replaces
+/// the last segment when the original file name is known.
+fn path_in_repo(
+    item_name: &str,
+    context: &str,
+    file_name: Option<&str>,
+) -> Result<Option<PathBuf>> {
+    let Some(relative) = item_name
+        .strip_prefix(context)
+        .and_then(|rest| rest.strip_prefix('/'))
+    else {
+        return Ok(None);
+    };
 
-    if !items_output.status.success() {
-        anyhow::bail!("Failed to list items for folder '{}'.", folder_id);
+    let mut relative = PathBuf::from(relative);
+    if let Some(file_name) = file_name {
+        relative.set_file_name(file_name);
     }
+    let escapes = relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir));
+    if escapes || relative.file_name().is_none() {
+        anyhow::bail!(
+            "Refusing to write '{}' to {:?}, which is outside the repository; use --flat or --output",
+            item_name,
+            relative
+        );
+    }
+    Ok(Some(relative))
+}
 
-    let items: Vec<serde_json::Value> = serde_json::from_slice(&items_output.stdout)
-        .context("Failed to parse items JSON")?;
+/// Fetch the latest dotenv content of an item straight from Bitwarden
+pub(crate) fn fetch_content(item: &serde_json::Value) -> Result<String> {
+    let json = fetch_item(item)?;
+    read_content(&json)
+}
 
-    Ok(items)
+/// Fetch the latest version of an item straight from Bitwarden
+pub(crate) fn fetch_item(item: &serde_json::Value) -> Result<serde_json::Value> {
+    let item_id = item["id"].as_str().ok_or_else(|| anyhow!("Missing item id"))?;
+    get_item(item_id)
 }
 
 fn sanitize_filename(name: &str) -> String {
-    let last_segment = name
-        .rsplit(['/', '\\'])
-        .next()
-        .unwrap_or(name);
+    let last_segment = item_file_name(name);
 
     let mut sanitized = last_segment.replace(['/', '\\'], "_");
     if sanitized.is_empty() {
@@ -304,3 +848,114 @@ fn sanitize_filename(name: &str) -> String {
     sanitized
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matches_across_path_segments() {
+        assert!(glob_match("acme/*/.env", "acme/api/.env"));
+        assert!(glob_match("acme/*/.env", "acme/api/backend/.env"));
+        assert!(glob_match("*.env.?", "prod.env.1"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("acme/*/.env", "acme/api/.env.local"));
+        assert!(!glob_match("*.env.?", "prod.env.10"));
+    }
+
+    fn menu() -> (Vec<OptionEntry>, Vec<serde_json::Value>) {
+        let names = ["acme/api/.env", "acme/api/.env.test", "acme/web/.env"];
+        let items = names
+            .iter()
+            .map(|name| serde_json::json!({ "name": name }))
+            .collect();
+        let option = |label: &str, kind| OptionEntry {
+            label: label.to_string(),
+            kind,
+        };
+        let options = vec![
+            option("acme/api (all)", SelectionKind::Group(vec![0, 1])),
+            option("acme/api/.env", SelectionKind::Single(0)),
+            option("acme/api/.env.test", SelectionKind::Single(1)),
+            option("acme/web/.env", SelectionKind::Single(2)),
+        ];
+        (options, items)
+    }
+
+    fn select(input: &str) -> Result<Vec<usize>> {
+        let (options, items) = menu();
+        parse_selection(input, &options, &items)
+    }
+
+    #[test]
+    fn selects_numbers_ranges_and_groups() {
+        assert_eq!(select("4").unwrap(), vec![2]);
+        assert_eq!(select("2-4").unwrap(), vec![0, 1, 2]);
+        assert_eq!(select(" 4 , 2 - 3 ").unwrap(), vec![2, 0, 1]);
+        assert_eq!(select("1").unwrap(), vec![0, 1]);
+    }
+
+    #[test]
+    fn selects_everything_with_all() {
+        assert_eq!(select("all").unwrap(), vec![0, 1, 2]);
+        assert_eq!(select("ALL").unwrap(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn selects_items_by_glob() {
+        assert_eq!(select("acme/*/.env").unwrap(), vec![0, 2]);
+        assert_eq!(select("4,acme/api/*").unwrap(), vec![2, 0, 1]);
+        assert!(select("other/*").is_err());
+    }
+
+    #[test]
+    fn keeps_items_picked_twice_once_in_first_order() {
+        assert_eq!(select("3,1,3").unwrap(), vec![1, 0]);
+        assert_eq!(select("1-3").unwrap(), vec![0, 1]);
+    }
+
+    #[test]
+    fn rejects_out_of_range_and_reversed_selections() {
+        assert!(select("0").is_err());
+        assert!(select("5").is_err());
+        assert!(select("3-5").is_err());
+        assert!(select("4-2").is_err());
+    }
+
+    #[test]
+    fn rejects_empty_selections() {
+        assert!(select("").is_err());
+        assert!(select(" , ,").is_err());
+    }
+
+    #[test]
+    fn maps_items_of_the_repository_to_their_path() {
+        assert_eq!(
+            path_in_repo("acme/api/config/.env", "acme/api", None).unwrap(),
+            Some(PathBuf::from("config/.env"))
+        );
+        assert_eq!(
+            path_in_repo("acme/api/certs/cert", "acme/api", Some("tls.p12")).unwrap(),
+            Some(PathBuf::from("certs/tls.p12"))
+        );
+    }
+
+    #[test]
+    fn leaves_items_of_other_repositories_unmapped() {
+        assert_eq!(path_in_repo("acme/web/.env", "acme/api", None).unwrap(), None);
+        assert_eq!(path_in_repo("acme/api-v2/.env", "acme/api", None).unwrap(), None);
+    }
+
+    #[test]
+    fn refuses_paths_outside_the_repository() {
+        assert!(path_in_repo("acme/api/../../.bashrc", "acme/api", None).is_err());
+        assert!(path_in_repo("acme/api//etc/passwd", "acme/api", None).is_err());
+        assert!(path_in_repo("acme/api/.env", "acme/api", Some("../.bashrc")).is_err());
+        assert!(path_in_repo("acme/api/.env", "acme/api", Some("/etc/passwd")).is_err());
+        assert!(path_in_repo("acme/api/", "acme/api", None).is_err());
+    }
+
+    #[test]
+    fn sanitizes_file_names_to_their_last_segment() {
+        assert_eq!(sanitize_filename("acme/api/.env"), ".env");
+    }
+}
@@ -0,0 +1,153 @@
+use crate::bw_commands::{find_folder_by_name, list_items, sync_vault};
+use crate::chunks::is_chunk;
+use crate::commands::retrieve::{fetch_content, find_item};
+use crate::content::{ensure_dotenv_layout, layout_of, Layout};
+use crate::dotenv::parse;
+use crate::git::repo_root;
+use crate::naming::{current_context_dir, item_dir};
+use anyhow::{anyhow, Context, Result};
+use std::process::Command;
+
+const ROOT_FOLDER_NAME: &str = "bw-env";
+
+// Kept by `--clean` so the command can still be found and run, like sudo's env_reset;
+// Windows cannot start most programs without SYSTEMROOT and PATHEXT
+const CLEAN_KEPT_VARIABLES: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "SHELL",
+    "TERM",
+    "LANG",
+    "SYSTEMROOT",
+    "PATHEXT",
+];
+
+/// Run a command with the variables of a stored .env file added to its environment.
+///
+/// Nothing is written to disk. On Unix the command replaces this process, so it receives
+/// signals directly and its exit code is the one the caller sees.
+pub fn run_env(
+    command: &[String],
+    item: Option<&str>,
+    no_override: bool,
+    clean: bool,
+) -> Result<()> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| anyhow!("No command given to run"))?;
+
+    sync_vault()?;
+    let folder_id = find_folder_by_name(ROOT_FOLDER_NAME)?
+        .ok_or_else(|| anyhow!("No '{}' folder found in Bitwarden", ROOT_FOLDER_NAME))?;
+    let mut items = list_items(&folder_id)?;
+    items.retain(|item| !is_chunk(item));
+
+    let item = resolve_item(&items, item)?;
+    ensure_dotenv_layout(item)?;
+    let content = fetch_content(item)?;
+    let variables = parse(&content);
+
+    let mut child = Command::new(program);
+    child.args(args);
+    if clean {
+        child.env_clear();
+        for name in CLEAN_KEPT_VARIABLES {
+            if let Some(value) = std::env::var_os(name) {
+                child.env(name, value);
+            }
+        }
+    }
+    for (key, value) in &variables {
+        // Variables already set by the caller win unless overriding was requested
+        if no_override && !clean && std::env::var_os(key).is_some() {
+            continue;
+        }
+        child.env(key, value);
+    }
+
+    exec(child, program)
+}
+
+/// The item to use: the one given by name or id, otherwise the .env item stored for the
+/// current directory of the current repository
+pub(crate) fn resolve_item<'a>(
+    items: &'a [serde_json::Value],
+    item: Option<&str>,
+) -> Result<&'a serde_json::Value> {
+    if let Some(query) = item {
+        return find_item(items, query);
+    }
+
+    let dir = context_dir().ok_or_else(|| {
+        anyhow!("Not inside a GitHub repository; pass the item name or id with --item")
+    })?;
+    let candidates: Vec<&serde_json::Value> = items
+        .iter()
+        .filter(|item| layout_of(item) != Layout::Attachment)
+        .filter(|item| item["name"].as_str().and_then(item_dir).as_deref() == Some(dir.as_str()))
+        .collect();
+
+    // Several files in the same directory: the plain .env is the natural default
+    let default_name = format!("{}/.env", dir);
+    match candidates.as_slice() {
+        [] => anyhow::bail!("No .env item found for {}; pass one with --item", dir),
+        [item] => Ok(item),
+        _ => {
+            if let Some(item) = candidates
+                .iter()
+                .find(|item| item["name"].as_str() == Some(default_name.as_str()))
+            {
+                return Ok(item);
+            }
+            let names: Vec<String> = candidates
+                .iter()
+                .map(|item| format!("  - {}", item["name"].as_str().unwrap_or("(unnamed)")))
+                .collect();
+            anyhow::bail!(
+                "Several items found for {}; pick one with --item:\n{}",
+                dir,
+                names.join("\n")
+            )
+        }
+    }
+}
+
+/// `owner/repo` of the current repository, followed by the current directory's path in it
+pub(crate) fn context_dir() -> Option<String> {
+    let context = current_context_dir()?;
+    let relative = repo_root(std::path::Path::new("."))
+        .and_then(|root| {
+            let root = std::fs::canonicalize(root).ok()?;
+            let cwd = std::env::current_dir().ok()?.canonicalize().ok()?;
+            cwd.strip_prefix(root)
+                .ok()
+                .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+        })
+        .unwrap_or_default();
+
+    if relative.is_empty() {
+        Some(context)
+    } else {
+        Some(format!("{}/{}", context, relative))
+    }
+}
+
+#[cfg(unix)]
+fn exec(mut child: Command, program: &str) -> Result<()> {
+    use std::os::unix::process::CommandExt;
+
+    // Only returns on failure
+    let err = child.exec();
+    Err(err).with_context(|| format!("Failed to run '{}'", program))
+}
+
+#[cfg(not(unix))]
+fn exec(mut child: Command, program: &str) -> Result<()> {
+    // Console signals such as Ctrl+C reach the whole process group, child included
+    let status = child
+        .status()
+        .with_context(|| format!("Failed to run '{}'", program))?;
+    std::process::exit(status.code().unwrap_or(1));
+}
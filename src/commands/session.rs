@@ -0,0 +1,43 @@
+use crate::auth::{unlock_vault, PasswordSource};
+use crate::session::{forget_session, save_session};
+use anyhow::{anyhow, Context, Result};
+use std::process::{Command, Stdio};
+
+/// Unlock the vault and cache the session so later invocations reuse it for `ttl` seconds
+pub fn unlock_env(ttl: u64, password: Option<&PasswordSource>) -> Result<()> {
+    unlock_vault(password)?;
+
+    let session = std::env::var("BW_SESSION")
+        .ok()
+        .filter(|session| !session.trim().is_empty())
+        .ok_or_else(|| anyhow!("No Bitwarden session to cache; run 'bw lock' and try again"))?;
+    save_session(session.trim(), ttl)?;
+
+    println!(
+        "🔓 Session cached for {} minute(s); run 'bw-env lock' to forget it.",
+        ttl.div_ceil(60)
+    );
+    Ok(())
+}
+
+/// Forget the cached session and lock the vault
+pub fn lock_env() -> Result<()> {
+    let forgotten = forget_session()?;
+
+    // Locking also invalidates the session key, wherever else it was exported
+    let status = Command::new("bw")
+        .arg("lock")
+        .stdout(Stdio::from(std::io::stderr()))
+        .status()
+        .context("Failed to run bw lock")?;
+    if !status.success() {
+        anyhow::bail!("Failed to lock the Bitwarden vault");
+    }
+
+    if forgotten {
+        println!("🔒 Vault locked and cached session removed.");
+    } else {
+        println!("🔒 Vault locked.");
+    }
+    Ok(())
+}
@@ -1,49 +1,464 @@
-use crate::bw_commands::{create_item, ensure_folder_exists, sync_vault};
+use crate::bw_commands::{
+    create_attachment, create_item, delete_attachment, delete_item, edit_item,
+    ensure_folder_exists, get_item, list_items, sync_vault,
+};
+use crate::commands::history::push_version;
+use crate::chunks::{delete_chunks, discard_new_chunks, is_chunk, store_chunks};
+use crate::commands::retrieve::{find_item, item_preview};
+use crate::content::{
+    encode_attachment, encode_content, layout_of, set_encoded_content, write_attachment, Layout,
+    MAX_FIELD_BYTES, MAX_NOTES_BYTES,
+};
+use crate::commands::merge::merge_file;
+use crate::dotenv::has_conflict_markers;
+use crate::git::repo_relative_path;
+use crate::naming::current_context_dir;
+use crate::picker::{is_interactive, pick, Entry};
+use crate::encryption::{encrypt, encryption_fields, encryption_of, Encryption};
+use crate::provenance::{
+    apply_provenance, collect_provenance, provenance_of, set_checksum, without_checksum,
+};
+use crate::state::{record_sync, synced_revision};
 use anyhow::{Context, Result};
 use std::fs;
 use std::io::{self, Write};
 use std::path::Path;
-use std::process::Command;
 
 // Configuration: Root folder name in Bitwarden
 const ROOT_FOLDER_NAME: &str = "bw-env";
 
-pub fn store_env(path: &str) -> Result<()> {
+pub fn store_env(path: &str, layout: Option<Layout>, encryption: Option<Encryption>) -> Result<()> {
     // Sync with Bitwarden server before storing
     sync_vault()?;
 
-    let env_content = fs::read_to_string(path)
-        .with_context(|| format!("Failed to read .env file at {}", path))?;
+    let bytes = fs::read(path).with_context(|| format!("Failed to read file at {}", path))?;
+    // Where the file comes from, recorded on the item so it can be traced and filtered on
+    let provenance = collect_provenance(path, &bytes);
+
+    // Anything that is not UTF-8 text can only be kept as an attachment
+    let env_content = String::from_utf8(bytes).ok();
+    let layout = match (&env_content, layout) {
+        (None, None) => {
+            println!("{} is not a UTF-8 text file, storing it as an attachment.", path);
+            Some(Layout::Attachment)
+        }
+        (None, Some(layout)) if layout != Layout::Attachment => anyhow::bail!(
+            "{} is not a UTF-8 text file; store it with '--layout attachment'",
+            path
+        ),
+        _ => layout,
+    };
+
+    if layout == Some(Layout::Attachment)
+        && encryption
+            .as_ref()
+            .is_some_and(|encryption| *encryption != Encryption::None)
+    {
+        anyhow::bail!("Client-side encryption is only supported for text files");
+    }
+
+    if layout != Some(Layout::Attachment)
+        && env_content.as_deref().is_some_and(has_conflict_markers)
+    {
+        anyhow::bail!(
+            "{} still contains merge conflict markers; resolve them before storing",
+            path
+        );
+    }
 
     // Check for or create root folder (still need this as a container)
     let root_folder_id = ensure_folder_exists(ROOT_FOLDER_NAME)?;
 
+    let items = list_items(&root_folder_id)?;
+
     // Derive an item name; if git info exists, use that structure, otherwise fallback to filename.
     // Allow a custom item name, but always store in the fixed bw-env folder.
-    let item_name = get_item_name_with_path(path)?;
+    let item_name = get_item_name_with_path(path, &items)?;
 
-    // Create the item directly in the root folder with the chosen path in the name
-    create_item(&item_name, &env_content, &root_folder_id)?;
+    // Look for an item with the same name so re-storing updates it instead of duplicating it
+    let existing = match find_latest_item_by_name(&items, &item_name) {
+        Some(item) => Some(item),
+        None if item_name == default_item_name(path)? => legacy_item(path, &items)?,
+        None => None,
+    };
+
+    let stored = match existing {
+        Some(item) => {
+            let item_id = item["id"]
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("Missing item id"))?;
+
+            // Someone else stored a newer version since this file was last synced
+            let vault_changed = synced_revision(Path::new(path), item_id)
+                .is_some_and(|revision| item["revisionDate"].as_str() != Some(revision.as_str()));
+            if vault_changed {
+                println!(
+                    "\n⚠️  The vault copy of '{}' changed since {} was last synced.",
+                    item_name, path
+                );
+            }
+
+            // Attachments are arbitrary files, which cannot be merged key by key
+            let mergeable =
+                layout_of(item) != Layout::Attachment && layout != Some(Layout::Attachment);
+            match prompt_existing_item_action(&item_name, vault_changed, mergeable)? {
+                ExistingItemAction::Update => {
+                    // Keep the item's current layout and encryption unless others were requested
+                    let layout = layout.unwrap_or_else(|| layout_of(item));
+                    let encryption = encryption.clone().unwrap_or_else(|| encryption_of(item));
+                    let mut item = item.clone();
+                    apply_provenance(&mut item, &provenance);
+                    // Items found under their legacy name move to the current one
+                    if item["name"].as_str() != Some(item_name.as_str()) {
+                        println!(
+                            "Renaming '{}' to '{}'.",
+                            item["name"].as_str().unwrap_or("(unnamed)"),
+                            item_name
+                        );
+                        item["name"] = serde_json::Value::String(item_name.clone());
+                    }
+                    update_stored_item(path, &item, env_content.as_deref(), layout, &encryption)?
+                }
+                ExistingItemAction::Merge => {
+                    return merge_file(path, item, false, false);
+                }
+                ExistingItemAction::KeepBoth => create_stored_item(
+                    path,
+                    &item_name,
+                    env_content.as_deref(),
+                    layout.unwrap_or(Layout::Notes),
+                    &encryption.clone().unwrap_or(Encryption::None),
+                    &root_folder_id,
+                    &provenance,
+                )?,
+                ExistingItemAction::Abort => {
+                    println!("Aborted, nothing was stored.");
+                    return Ok(());
+                }
+            }
+        }
+        None => {
+            // Create the item directly in the root folder with the chosen path in the name
+            create_stored_item(
+                path,
+                &item_name,
+                env_content.as_deref(),
+                layout.unwrap_or(Layout::Notes),
+                &encryption.unwrap_or(Encryption::None),
+                &root_folder_id,
+                &provenance,
+            )?
+        }
+    };
+    record_sync(Path::new(path), &stored);
 
     println!("Stored folder: '{}'", ROOT_FOLDER_NAME);
     println!("Stored item name: '{}'", item_name);
     Ok(())
 }
 
-fn get_item_name_with_path(file_path: &str) -> Result<String> {
-    let file_name = Path::new(file_path)
+fn create_stored_item(
+    path: &str,
+    item_name: &str,
+    env_content: Option<&str>,
+    layout: Layout,
+    encryption: &Encryption,
+    folder_id: &str,
+    provenance: &[serde_json::Value],
+) -> Result<serde_json::Value> {
+    if layout == Layout::Attachment {
+        let (notes, mut fields) = encode_attachment(&file_name_of(path));
+        fields.extend_from_slice(provenance);
+        let created = create_item(item_name, &notes, fields, folder_id)?;
+        let item_id = created["id"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing item id"))?;
+        if let Err(err) = create_attachment(item_id, path) {
+            // Do not leave an item without its file behind
+            if let Err(delete_err) = delete_item(item_id) {
+                eprintln!(
+                    "Warning: failed to remove the empty item '{}': {}",
+                    item_name, delete_err
+                );
+            }
+            return Err(err);
+        }
+        // The upload revised the item; return that revision so it is the one recorded as synced
+        return get_item(item_id);
+    }
+
+    let env_content = require_text(path, env_content)?;
+    let (notes, mut fields) =
+        encode_for_storage(item_name, env_content, layout, encryption, folder_id)?;
+    if *encryption == Encryption::None {
+        fields.extend_from_slice(provenance);
+    } else {
+        fields.extend(without_checksum(provenance));
+    }
+    create_item(item_name, &notes, fields.clone(), folder_id).inspect_err(|_| {
+        discard_new_chunks(&fields);
+    })
+}
+
+fn update_stored_item(
+    path: &str,
+    item: &serde_json::Value,
+    env_content: Option<&str>,
+    layout: Layout,
+    encryption: &Encryption,
+) -> Result<serde_json::Value> {
+    if layout != Layout::Attachment {
+        return save_content(item, require_text(path, env_content)?, layout, encryption);
+    }
+
+    let item_id = item["id"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing item id"))?;
+
+    // Upload before touching the item: until the edit succeeds it keeps its previous notes,
+    // chunks and attachments, so a failed upload loses nothing
+    create_attachment(item_id, path)?;
+
+    // The upload revised the item, so edit the current revision
+    let edited = get_item(item_id).and_then(|mut updated| {
+        // Keep the content being replaced so it can be rolled back
+        push_version(&mut updated);
+        write_attachment(&mut updated, &file_name_of(path));
+        edit_item(item_id, &updated)
+    });
+    if let Err(err) = edited {
+        discard_new_attachments(item);
+        return Err(err);
+    }
+    remove_replaced_storage(item)?;
+
+    // Changing attachments revises the item again after the edit
+    get_item(item_id)
+}
+
+/// Replace the dotenv content of an existing item, keeping the replaced version in its history
+pub(crate) fn save_content(
+    item: &serde_json::Value,
+    content: &str,
+    layout: Layout,
+    encryption: &Encryption,
+) -> Result<serde_json::Value> {
+    let item_id = item["id"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing item id"))?;
+    let item_name = item["name"].as_str().unwrap_or("(unnamed)");
+    let folder_id = item["folderId"].as_str().unwrap_or_default();
+
+    let mut updated = item.clone();
+    // Keep the content being replaced so it can be rolled back
+    push_version(&mut updated);
+    let encoded = encode_for_storage(item_name, content, layout, encryption, folder_id)?;
+    let new_fields = encoded.1.clone();
+    set_encoded_content(&mut updated, encoded);
+    set_checksum(&mut updated, content.as_bytes(), encryption);
+    let edited = edit_item(item_id, &updated).inspect_err(|_| {
+        // The item still points at its previous storage, so the new chunks are unused
+        discard_new_chunks(&new_fields);
+    })?;
+
+    remove_replaced_storage(item)?;
+    Ok(edited)
+}
+
+/// Encode content for the given layout, encrypting it client-side when requested and
+/// spreading oversized notes across chunk items
+fn encode_for_storage(
+    item_name: &str,
+    content: &str,
+    layout: Layout,
+    encryption: &Encryption,
+    folder_id: &str,
+) -> Result<(String, Vec<serde_json::Value>)> {
+    if *encryption != Encryption::None {
+        if layout == Layout::Fields {
+            anyhow::bail!("Client-side encryption is only supported with the notes layout");
+        }
+        let armored = encrypt(content, encryption)?;
+        let (notes, mut fields) = encode_for_storage(
+            item_name,
+            &armored,
+            Layout::Notes,
+            &Encryption::None,
+            folder_id,
+        )?;
+        fields.extend(encryption_fields(encryption));
+        return Ok((notes, fields));
+    }
+
+    let (notes, fields) = encode_content(content, layout);
+
+    let oversized_field = fields
+        .iter()
+        .find(|field| field["value"].as_str().map_or(0, str::len) > MAX_FIELD_BYTES);
+    if let Some(field) = oversized_field {
+        anyhow::bail!(
+            "'{}' is too large for a custom field; store the file with '--layout notes'",
+            field["name"].as_str().unwrap_or("(unnamed)")
+        );
+    }
+
+    if notes.len() > MAX_NOTES_BYTES {
+        return store_chunks(item_name, content, folder_id);
+    }
+    Ok((notes, fields))
+}
+
+/// Remove the attachments or chunk items that held the item's previous content
+fn remove_replaced_storage(old_item: &serde_json::Value) -> Result<()> {
+    let item_id = old_item["id"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing item id"))?;
+
+    match layout_of(old_item) {
+        Layout::Attachment => {
+            for attachment_id in attachment_ids(old_item) {
+                delete_attachment(item_id, &attachment_id)?;
+            }
+        }
+        Layout::Chunked => delete_chunks(old_item)?,
+        Layout::Notes | Layout::Fields => {}
+    }
+    Ok(())
+}
+
+/// Best-effort removal of attachments uploaded to an item whose edit then failed
+fn discard_new_attachments(old_item: &serde_json::Value) {
+    let Some(item_id) = old_item["id"].as_str() else {
+        return;
+    };
+    let previous = attachment_ids(old_item);
+    let current = match get_item(item_id) {
+        Ok(current) => current,
+        Err(err) => {
+            eprintln!("Warning: failed to remove the uploaded attachment: {}", err);
+            return;
+        }
+    };
+    for attachment_id in attachment_ids(&current) {
+        if previous.contains(&attachment_id) {
+            continue;
+        }
+        if let Err(err) = delete_attachment(item_id, &attachment_id) {
+            eprintln!(
+                "Warning: failed to remove the uploaded attachment '{}': {}",
+                attachment_id, err
+            );
+        }
+    }
+}
+
+fn attachment_ids(item: &serde_json::Value) -> Vec<String> {
+    item["attachments"]
+        .as_array()
+        .map(|attachments| {
+            attachments
+                .iter()
+                .filter_map(|attachment| attachment["id"].as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn require_text<'a>(path: &str, env_content: Option<&'a str>) -> Result<&'a str> {
+    env_content.ok_or_else(|| {
+        anyhow::anyhow!(
+            "{} is not a UTF-8 text file; store it with '--layout attachment'",
+            path
+        )
+    })
+}
+
+fn file_name_of(path: &str) -> String {
+    Path::new(path)
         .file_name()
         .and_then(|n| n.to_str())
-        .unwrap_or("unknown");
+        .unwrap_or("unknown")
+        .to_string()
+}
 
-    let current_dir = std::env::current_dir().context("Failed to get current directory")?;
+enum ExistingItemAction {
+    Update,
+    Merge,
+    KeepBoth,
+    Abort,
+}
 
-    // Prefer git-style path without the bw-env prefix; otherwise use filename. Offer a custom name option; folder remains bw-env via folderId.
-    let default_name = if let Some(git_path) = get_git_repo_path(&current_dir, file_name) {
-        git_path
-    } else {
-        file_name.to_string()
+/// Find the most recently revised item with the given name, if any
+fn find_latest_item_by_name<'a>(
+    items: &'a [serde_json::Value],
+    name: &str,
+) -> Option<&'a serde_json::Value> {
+    items
+        .iter()
+        .filter(|item| item["name"].as_str() == Some(name))
+        .max_by_key(|item| item["revisionDate"].as_str().unwrap_or("").to_string())
+}
+
+fn prompt_existing_item_action(
+    item_name: &str,
+    vault_changed: bool,
+    mergeable: bool,
+) -> Result<ExistingItemAction> {
+    // Merging is the safer default when the vault copy moved on
+    let merge_default = vault_changed && mergeable;
+    let mut actions = vec![(
+        ExistingItemAction::Update,
+        format!(
+            "Update the existing item{}",
+            if merge_default { "" } else { " (default)" }
+        ),
+    )];
+    if mergeable {
+        actions.push((
+            ExistingItemAction::Merge,
+            format!(
+                "Merge local and vault changes{}",
+                if merge_default { " (default)" } else { "" }
+            ),
+        ));
+    }
+    actions.push((
+        ExistingItemAction::KeepBoth,
+        "Keep both (create a new item with the same name)".to_string(),
+    ));
+    actions.push((ExistingItemAction::Abort, "Abort".to_string()));
+
+    println!("\nAn item named '{}' already exists.", item_name);
+    for (idx, (_, label)) in actions.iter().enumerate() {
+        println!("{}. {}", idx + 1, label);
+    }
+    println!();
+    print!("Enter your choice: ");
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .context("Failed to read user input")?;
+
+    let choice = match input.trim() {
+        "" if merge_default => 2,
+        "" => 1,
+        other => other.parse::<usize>().unwrap_or(0),
     };
+    if choice == 0 || choice > actions.len() {
+        anyhow::bail!("Invalid choice");
+    }
+    Ok(actions.swap_remove(choice - 1).0)
+}
+
+fn get_item_name_with_path(file_path: &str, items: &[serde_json::Value]) -> Result<String> {
+    // Prefer git-style path without the bw-env prefix; otherwise use filename. Offer a custom name option; folder remains bw-env via folderId.
+    let default_name = default_item_name(file_path)?;
+    if is_interactive() {
+        return pick_item_name(default_name, items);
+    }
 
     println!("\nChoose the item name:");
     println!("1. {} (default)", default_name);
@@ -79,42 +494,180 @@ fn get_item_name_with_path(file_path: &str) -> Result<String> {
     }
 }
 
-fn get_git_repo_path(current_dir: &Path, file_name: &str) -> Option<String> {
-    // Check if we're in a git repository
-    let git_output = Command::new("git")
-        .args(["remote", "get-url", "origin"])
-        .current_dir(current_dir)
-        .output()
-        .ok()?;
+/// Fuzzy finder over the default name, a custom name and the names of existing items
+fn pick_item_name(default_name: String, items: &[serde_json::Value]) -> Result<String> {
+    let new_item = "New item".to_string();
+    let mut entries = vec![
+        Entry {
+            group: new_item.clone(),
+            label: format!("{} (default)", default_name),
+            preview: vec![
+                default_name.clone(),
+                String::new(),
+                format!("Stored in the 📁{} folder.", ROOT_FOLDER_NAME),
+            ],
+        },
+        Entry {
+            group: new_item,
+            label: "Custom name…".to_string(),
+            preview: vec![format!(
+                "Type a name; the item is stored in the 📁{} folder.",
+                ROOT_FOLDER_NAME
+            )],
+        },
+    ];
+    entries.extend(items.iter().filter(|item| !is_chunk(item)).map(|item| Entry {
+        group: "Existing items (store over the picked item)".to_string(),
+        label: item["name"].as_str().unwrap_or("(unnamed)").to_string(),
+        preview: item_preview(item),
+    }));
 
-    if !git_output.status.success() {
-        return None;
+    let picked = pick("Item name", &entries, false)?
+        .and_then(|picked| picked.first().copied())
+        .ok_or_else(|| anyhow::anyhow!("Aborted, nothing was stored."))?;
+    match picked {
+        0 => Ok(default_name),
+        1 => {
+            print!("Enter custom item name (folder stays 'bw-env'): ");
+            io::stdout().flush().unwrap();
+            let mut custom = String::new();
+            io::stdin()
+                .read_line(&mut custom)
+                .context("Failed to read custom name")?;
+            Ok(custom.trim().to_string())
+        }
+        _ => Ok(entries[picked].label.clone()),
     }
+}
 
-    let remote_url = String::from_utf8(git_output.stdout).ok()?;
-    let remote_url = remote_url.trim();
+/// Vault item of a local file: the item with its default name, falling back to one stored
+/// under the legacy name
+pub(crate) fn find_item_for_file<'a>(
+    items: &'a [serde_json::Value],
+    path: &str,
+) -> Result<&'a serde_json::Value> {
+    let name = default_item_name(path)?;
+    if items.iter().all(|item| item["name"].as_str() != Some(name.as_str())) {
+        if let Some(item) = legacy_item(path, items)? {
+            return Ok(item);
+        }
+    }
+    find_item(items, &name)
+}
 
-    // Parse GitHub URL (handles both HTTPS and SSH)
-    let repo_info = if remote_url.starts_with("https://github.com/") {
-        remote_url
-            .strip_prefix("https://github.com/")?
-            .strip_suffix(".git")
-            .unwrap_or(remote_url.strip_prefix("https://github.com/")?)
-    } else if remote_url.starts_with("git@github.com:") {
-        remote_url
-            .strip_prefix("git@github.com:")?
-            .strip_suffix(".git")
-            .unwrap_or(remote_url.strip_prefix("git@github.com:")?)
-    } else {
-        return None;
+/// Item stored for a file under the name used before item names included the file's
+/// directory in the repository (`owner/repo/<file name>`).
+///
+/// Every file with that name in the repository shared it, so the item only counts when its
+/// recorded source path matches the file, or the user confirms it.
+fn legacy_item<'a>(
+    path: &str,
+    items: &'a [serde_json::Value],
+) -> Result<Option<&'a serde_json::Value>> {
+    let file_name = file_name_of(path);
+    let Some(legacy_name) = current_context_dir().map(|repo| format!("{}/{}", repo, file_name))
+    else {
+        return Ok(None);
+    };
+    if legacy_name == default_item_name(path)? {
+        return Ok(None);
+    }
+    let Some(item) = find_latest_item_by_name(items, &legacy_name) else {
+        return Ok(None);
     };
 
-    // Split into owner/repo
-    let parts: Vec<&str> = repo_info.split('/').collect();
-    if parts.len() == 2 {
-        // Return path without bw-env prefix; folder is controlled separately via folderId
-        Some(format!("{}/{}/{}", parts[0], parts[1], file_name))
-    } else {
-        None
+    let relative_path = repo_relative_path(Path::new(path)).unwrap_or(file_name);
+    let source_path = provenance_of(item)
+        .into_iter()
+        .find(|(key, _)| *key == "source-path")
+        .map(|(_, value)| value);
+    if let Some(source_path) = source_path {
+        return Ok((source_path == relative_path).then_some(item));
+    }
+
+    print!(
+        "\n'{}' was stored under the previous naming scheme, which used the file name only.\nIs it the vault copy of {}? [y/N]: ",
+        legacy_name, relative_path
+    );
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .context("Failed to read user input")?;
+    Ok(matches!(input.trim(), "y" | "Y" | "yes").then_some(item))
+}
+
+/// Default item name for a file: its git repository path (`owner/repo/path/in/repo`) when
+/// available, otherwise its file name
+pub(crate) fn default_item_name(file_path: &str) -> Result<String> {
+    let file_name = Path::new(file_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown");
+
+    // Keep the path within the repository so files in different directories get distinct names
+    let relative_path =
+        repo_relative_path(Path::new(file_path)).unwrap_or_else(|| file_name.to_string());
+
+    Ok(current_context_dir()
+        .map(|repo| format!("{}/{}", repo, relative_path))
+        .unwrap_or_else(|| file_name.to_string()))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::FakeBw;
+    use serde_json::json;
+
+    #[test]
+    fn failed_upload_leaves_the_item_and_its_chunks_untouched() {
+        let bw = FakeBw::new();
+        let chunk = json!({ "id": "chunk-1", "name": "acme/api/.env [chunk 1/1]", "notes": "" });
+        let item = json!({
+            "id": "env-1",
+            "name": "acme/api/.env",
+            "notes": "",
+            "fields": [
+                { "name": "bw-env:layout", "value": "chunked", "type": 0 },
+                { "name": "bw-env:chunks", "value": "chunk-1", "type": 0 },
+            ],
+        });
+        bw.add_item(&chunk);
+        bw.add_item(&item);
+        bw.fail("create", "attachment");
+
+        let err =
+            update_stored_item("secret.p12", &item, None, Layout::Attachment, &Encryption::None)
+                .unwrap_err();
+
+        assert!(err.to_string().contains("fake failure"), "{:#}", err);
+        assert_eq!(bw.item("env-1"), Some(item));
+        assert_eq!(bw.item("chunk-1"), Some(chunk));
+        assert_eq!(bw.calls(), vec!["create attachment"]);
+    }
+
+    #[test]
+    fn successful_upload_switches_the_layout_and_removes_old_chunks() {
+        let bw = FakeBw::new();
+        bw.add_item(&json!({ "id": "chunk-1", "name": "acme/api/.env [chunk 1/1]", "notes": "" }));
+        let item = json!({
+            "id": "env-1",
+            "name": "acme/api/.env",
+            "notes": "",
+            "fields": [
+                { "name": "bw-env:layout", "value": "chunked", "type": 0 },
+                { "name": "bw-env:chunks", "value": "chunk-1", "type": 0 },
+            ],
+        });
+        bw.add_item(&item);
+
+        update_stored_item("secret.p12", &item, None, Layout::Attachment, &Encryption::None)
+            .unwrap();
+
+        let stored = bw.item("env-1").unwrap();
+        assert_eq!(layout_of(&stored), Layout::Attachment);
+        assert_eq!(bw.item_ids(), vec!["env-1"]);
+        assert_eq!(bw.calls()[0], "create attachment");
     }
 }
@@ -0,0 +1,395 @@
+use crate::chunks::{clear_chunk_fields, read_chunks};
+use crate::dotenv::{escape_double_quoted, format_entry, format_value, parse_lines, Line};
+use crate::encryption::{clear_encryption_fields, decrypt, is_encrypted};
+use crate::fields::{
+    fields_mut, get_fields, new_field, remove_fields, FIELD_PREFIX, FIELD_TYPE_HIDDEN,
+    FIELD_TYPE_TEXT,
+};
+use anyhow::Result;
+use clap::ValueEnum;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// Marks how the dotenv content is laid out on the item; absent means `notes`
+const LAYOUT_FIELD_NAME: &str = "bw-env:layout";
+
+// The file with its values left out, for the `fields` layout
+const TEMPLATE_FIELD_NAME: &str = "bw-env:template";
+
+// Stands in for the value in the template's copy of a `KEY=VALUE` line
+const VALUE_PLACEHOLDER: &str = "\u{0}";
+
+// Original file name for the `attachment` layout
+const FILENAME_FIELD_NAME: &str = "bw-env:filename";
+
+/// Largest notes payload that stays under Bitwarden's 10,000 character limit once encrypted
+pub const MAX_NOTES_BYTES: usize = 7_000;
+
+/// Largest custom field value that stays under Bitwarden's 5,000 character limit once encrypted
+pub const MAX_FIELD_BYTES: usize = 3_500;
+
+/// How a dotenv file is stored on a Bitwarden item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Layout {
+    /// The whole file in the item's notes
+    Notes,
+    /// One hidden custom field per variable
+    Fields,
+    /// The file as-is in a Bitwarden attachment (any file type; requires premium)
+    Attachment,
+    /// Compressed notes spread across linked chunk items; chosen automatically for large files
+    #[value(skip)]
+    Chunked,
+}
+
+impl Layout {
+    /// Name of the layout, as stored on items and accepted by `--layout`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Layout::Notes => "notes",
+            Layout::Fields => "fields",
+            Layout::Attachment => "attachment",
+            Layout::Chunked => "chunked",
+        }
+    }
+}
+
+/// Custom field marking an item with the given layout
+pub fn layout_field(layout: Layout) -> Value {
+    new_field(LAYOUT_FIELD_NAME, layout.as_str(), FIELD_TYPE_TEXT)
+}
+
+/// Layout used by an existing item
+pub fn layout_of(item: &Value) -> Layout {
+    match get_fields(item, LAYOUT_FIELD_NAME).first() {
+        Some(&"fields") => Layout::Fields,
+        Some(&"attachment") => Layout::Attachment,
+        Some(&"chunked") => Layout::Chunked,
+        _ => Layout::Notes,
+    }
+}
+
+/// Split dotenv content into the notes and custom fields for the given layout.
+///
+/// Chunking needs extra items, so `Chunked` is encoded like `Notes` here; callers
+/// split oversized notes with `chunks::store_chunks`.
+pub fn encode_content(content: &str, layout: Layout) -> (String, Vec<Value>) {
+    match layout {
+        Layout::Notes | Layout::Chunked => (content.to_string(), Vec::new()),
+        Layout::Fields => {
+            let mut lines = Vec::new();
+            let mut fields = vec![layout_field(layout)];
+            for line in parse_lines(content) {
+                match line {
+                    Line::Entry {
+                        key,
+                        value,
+                        mut raw,
+                        value_span,
+                    } => {
+                        // Everything but the value stays in the template, exactly as written
+                        let written = raw[value_span.clone()].to_string();
+                        let verbatim =
+                            quote_value(&value, quote_before(&raw[..value_span.start])) != written;
+                        raw.replace_range(value_span, VALUE_PLACEHOLDER);
+                        let mut entry = serde_json::json!({ "key": key, "line": raw });
+                        if verbatim {
+                            // Escapes `quote_value` would write differently; keep them as-is
+                            entry["verbatim"] = Value::Bool(true);
+                        }
+                        lines.push(entry);
+                        let value = if verbatim { written } else { value };
+                        fields.push(new_field(&key, &value, FIELD_TYPE_HIDDEN));
+                    }
+                    Line::Other(text) => lines.push(Value::String(text)),
+                }
+            }
+            let mut template = serde_json::json!({
+                "lines": lines,
+                "finalNewline": content.ends_with('\n'),
+            });
+            if content.contains("\r\n") {
+                template["newline"] = Value::String("\r\n".to_string());
+            }
+            fields.push(new_field(
+                TEMPLATE_FIELD_NAME,
+                &template.to_string(),
+                FIELD_TYPE_HIDDEN,
+            ));
+            (String::new(), fields)
+        }
+        Layout::Attachment => (String::new(), vec![layout_field(layout)]),
+    }
+}
+
+/// Split the metadata of a file stored as an attachment into notes and custom fields
+pub fn encode_attachment(file_name: &str) -> (String, Vec<Value>) {
+    let (notes, mut fields) = encode_content("", Layout::Attachment);
+    fields.push(new_field(FILENAME_FIELD_NAME, file_name, FIELD_TYPE_TEXT));
+    (notes, fields)
+}
+
+/// Original file name of an item stored as an attachment
+pub fn attachment_file_name(item: &Value) -> Option<&str> {
+    get_fields(item, FILENAME_FIELD_NAME).first().copied()
+}
+
+/// Fail for items that do not hold a dotenv file
+pub fn ensure_dotenv_layout(item: &Value) -> Result<()> {
+    if layout_of(item) == Layout::Attachment {
+        anyhow::bail!(
+            "'{}' is stored as an attachment, not as a .env file",
+            item["name"].as_str().unwrap_or("(unnamed)")
+        );
+    }
+    Ok(())
+}
+
+/// Plaintext dotenv content kept on the item itself, or `None` when it lives in
+/// attachments or chunk items, or is encrypted client-side
+pub fn inline_content(item: &Value) -> Option<String> {
+    if is_encrypted(item) {
+        return None;
+    }
+    match layout_of(item) {
+        Layout::Notes => Some(item["notes"].as_str().unwrap_or("").to_string()),
+        Layout::Fields => Some(rebuild_from_fields(item)),
+        Layout::Attachment | Layout::Chunked => None,
+    }
+}
+
+/// Dotenv content of the item, whatever its layout, reassembling chunked items and
+/// decrypting client-side encrypted ones
+pub fn read_content(item: &Value) -> Result<String> {
+    let stored = match layout_of(item) {
+        Layout::Chunked => read_chunks(item)?,
+        Layout::Fields => rebuild_from_fields(item),
+        Layout::Notes | Layout::Attachment => item["notes"].as_str().unwrap_or("").to_string(),
+    };
+    if is_encrypted(item) {
+        decrypt(&stored)
+    } else {
+        Ok(stored)
+    }
+}
+
+/// Turn the item into one whose file is stored as an attachment
+pub fn write_attachment(item: &mut Value, file_name: &str) {
+    set_encoded_content(item, encode_attachment(file_name));
+}
+
+/// Replace the item's notes and layout fields, keeping any other bw-env metadata fields
+pub fn set_encoded_content(item: &mut Value, (notes, fields): (String, Vec<Value>)) {
+    // Variables from a previous `fields` layout are replaced; other custom fields stay
+    if layout_of(item) == Layout::Fields {
+        fields_mut(item).retain(|field| {
+            field["name"]
+                .as_str()
+                .is_some_and(|name| name.starts_with(FIELD_PREFIX))
+        });
+    }
+    remove_fields(item, LAYOUT_FIELD_NAME);
+    remove_fields(item, TEMPLATE_FIELD_NAME);
+    remove_fields(item, FILENAME_FIELD_NAME);
+    clear_chunk_fields(item);
+    clear_encryption_fields(item);
+
+    item["notes"] = Value::String(notes);
+    fields_mut(item).extend(fields);
+}
+
+fn rebuild_from_fields(item: &Value) -> String {
+    let variables: Vec<(&str, &str)> = item["fields"]
+        .as_array()
+        .map(|fields| {
+            fields
+                .iter()
+                .filter_map(|field| Some((field["name"].as_str()?, field["value"].as_str()?)))
+                .filter(|(name, _)| !name.starts_with(FIELD_PREFIX))
+                .collect()
+        })
+        .unwrap_or_default();
+    // A key assigned several times has one field per assignment, in file order
+    let mut values: HashMap<&str, VecDeque<&str>> = HashMap::new();
+    for (key, value) in &variables {
+        values.entry(key).or_default().push_back(value);
+    }
+
+    let template: Value = get_fields(item, TEMPLATE_FIELD_NAME)
+        .first()
+        .and_then(|raw| serde_json::from_str(raw).ok())
+        .unwrap_or_default();
+    // Templates written before lines were kept are a bare array of lines
+    let (template_lines, final_newline) = match &template {
+        Value::Array(lines) => (lines.as_slice(), true),
+        _ => (
+            template["lines"].as_array().map_or(&[][..], Vec::as_slice),
+            template["finalNewline"].as_bool().unwrap_or(true),
+        ),
+    };
+    let newline = template["newline"].as_str().unwrap_or("\n");
+
+    let mut lines = Vec::new();
+    let mut written = HashSet::new();
+    for entry in template_lines {
+        if let Some(text) = entry.as_str() {
+            lines.push(text.to_string());
+        } else if let Some(key) = entry["key"].as_str() {
+            // Variables deleted in the Bitwarden UI are simply left out
+            let Some(value) = values.get_mut(key).and_then(VecDeque::pop_front) else {
+                continue;
+            };
+            written.insert(key);
+            let line = match entry["line"].as_str() {
+                Some(line) => fill_line(line, key, value, entry["verbatim"] == true),
+                None => format_entry(key, value),
+            };
+            // Multi-line values were split on the file's own line endings
+            lines.push(line.replace('\n', newline));
+        }
+    }
+
+    // Variables added in the Bitwarden UI go at the end
+    for (key, value) in &variables {
+        if written.insert(key) {
+            lines.push(format_entry(key, value));
+        }
+    }
+
+    let mut content = lines.join(newline);
+    if !lines.is_empty() && final_newline {
+        content.push_str(newline);
+    }
+    content
+}
+
+/// Put a value back into its template line, quoting it the way the line does
+fn fill_line(line: &str, key: &str, value: &str, verbatim: bool) -> String {
+    let Some((before, after)) = line.split_once(VALUE_PLACEHOLDER) else {
+        return format_entry(key, value);
+    };
+    if verbatim {
+        return format!("{}{}{}", before, value, after);
+    }
+
+    let quote = quote_before(before);
+    let filled = format!("{}{}{}", before, quote_value(value, quote), after);
+    if reads_back(&filled, key, value) {
+        return filled;
+    }
+
+    // A value edited in the Bitwarden UI may no longer fit the original quoting; requote it
+    // and keep the rest of the line
+    let (before, after) = match quote {
+        Some(quote) => (
+            before.strip_suffix(quote).unwrap_or(before),
+            after.strip_prefix(quote).unwrap_or(after),
+        ),
+        None => (before, after),
+    };
+    let requoted = format!("{}{}{}", before, format_value(value), after);
+    if reads_back(&requoted, key, value) {
+        requoted
+    } else {
+        format_entry(key, value)
+    }
+}
+
+/// Whether the text parses as exactly the given assignment
+fn reads_back(text: &str, key: &str, value: &str) -> bool {
+    matches!(
+        parse_lines(text).as_slice(),
+        [Line::Entry { key: parsed_key, value: parsed_value, .. }]
+            if parsed_key == key && parsed_value == value
+    )
+}
+
+/// Quote character opening a value, given the text of its line before the value
+fn quote_before(before: &str) -> Option<char> {
+    before.chars().last().filter(|c| matches!(c, '"' | '\''))
+}
+
+/// How a value is written between the given quotes, or unquoted
+fn quote_value(value: &str, quote: Option<char>) -> String {
+    match quote {
+        Some('"') => escape_double_quoted(value),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(content: &str) -> String {
+        let (notes, fields) = encode_content(content, Layout::Fields);
+        read_content(&serde_json::json!({ "notes": notes, "fields": fields })).unwrap()
+    }
+
+    fn field_value<'a>(fields: &'a [Value], name: &str) -> Option<&'a str> {
+        fields
+            .iter()
+            .find(|field| field["name"] == name)
+            .and_then(|field| field["value"].as_str())
+    }
+
+    #[test]
+    fn fields_layout_round_trips_byte_for_byte() {
+        for content in [
+            "export A=1\nB='x y'\nC=plain # note\n",
+            "A=1",
+            "\n",
+            "",
+            "# comment\n\n  KEY = \"a\\\"b\\nc\" # trailing\nA=1\nA=2\n",
+            "MULTI=\"line one\nline two\"\nNEXT=1\n",
+            "WIN=1\r\nQUOTED=\"a b\"\r\n",
+            "ODD=\"\\$HOME \\q\"\n",
+            "EMPTY=\nSINGLE=''\n",
+        ] {
+            assert_eq!(round_trip(content), content);
+        }
+    }
+
+    #[test]
+    fn only_values_move_into_fields() {
+        let (_, fields) = encode_content("export A=1\nB='x y'\nC=plain # note\n", Layout::Fields);
+
+        assert_eq!(field_value(&fields, "A"), Some("1"));
+        assert_eq!(field_value(&fields, "B"), Some("x y"));
+        assert_eq!(field_value(&fields, "C"), Some("plain"));
+        let template = field_value(&fields, TEMPLATE_FIELD_NAME).unwrap();
+        assert!(!template.contains("x y") && !template.contains("plain"));
+    }
+
+    #[test]
+    fn values_edited_in_bitwarden_are_written_back() {
+        let (notes, mut fields) =
+            encode_content("export A=1\nB='x y' # keep\nC=2\n", Layout::Fields);
+        for field in &mut fields {
+            match field["name"].as_str() {
+                Some("A") => field["value"] = Value::from("two words"),
+                Some("B") => field["value"] = Value::from("it's"),
+                _ => {}
+            }
+        }
+        fields.retain(|field| field["name"] != "C");
+        fields.push(new_field("NEW", "x", FIELD_TYPE_HIDDEN));
+        let item = serde_json::json!({ "notes": notes, "fields": fields });
+
+        assert_eq!(
+            read_content(&item).unwrap(),
+            "export A=two words\nB=\"it's\" # keep\nNEW=x\n"
+        );
+    }
+
+    #[test]
+    fn reads_templates_written_before_lines_were_kept() {
+        let item = serde_json::json!({ "fields": [
+            layout_field(Layout::Fields),
+            new_field(TEMPLATE_FIELD_NAME, r##"["# db",{"key":"A"}]"##, FIELD_TYPE_HIDDEN),
+            new_field("A", "x y", FIELD_TYPE_HIDDEN),
+        ] });
+
+        assert_eq!(read_content(&item).unwrap(), "# db\nA=\"x y\"\n");
+    }
+}
@@ -0,0 +1,520 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// A single logical line of a dotenv file
+#[derive(Debug, Clone)]
+pub enum Line {
+    /// A `KEY=VALUE` assignment; `raw` keeps the original text (possibly multi-line)
+    Entry {
+        key: String,
+        value: String,
+        raw: String,
+        /// Where the value is written in `raw`, inside any quotes
+        value_span: Range<usize>,
+    },
+    /// Comments, blank lines and anything that is not an assignment
+    Other(String),
+}
+
+/// Parse dotenv content into lines, keeping comments and ordering
+pub fn parse_lines(content: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut rest = content.lines().peekable();
+
+    while let Some(line) = rest.next() {
+        let Some((key, value_part)) = split_assignment(line) else {
+            lines.push(Line::Other(line.to_string()));
+            continue;
+        };
+
+        let mut raw = line.to_string();
+        let value_part = value_part.trim_start();
+        let value_start = line.len() - value_part.len();
+        let (value, value_span) = match value_part.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                // Quoted values may span several lines until the closing quote
+                let mut body = value_part[1..].to_string();
+                while find_closing_quote(&body, quote).is_none() {
+                    match rest.next() {
+                        Some(next) => {
+                            raw.push('\n');
+                            raw.push_str(next);
+                            body.push('\n');
+                            body.push_str(next);
+                        }
+                        None => break,
+                    }
+                }
+                let end = find_closing_quote(&body, quote).unwrap_or(body.len());
+                let inner = &body[..end];
+                let value = if quote == '"' {
+                    unescape_double_quoted(inner)
+                } else {
+                    inner.to_string()
+                };
+                // The body is the raw text after the opening quote
+                (value, value_start + 1..value_start + 1 + end)
+            }
+            _ => {
+                let value = strip_inline_comment(value_part).trim_end();
+                (value.to_string(), value_start..value_start + value.len())
+            }
+        };
+
+        lines.push(Line::Entry {
+            key,
+            value,
+            raw,
+            value_span,
+        });
+    }
+
+    lines
+}
+
+/// Parse dotenv content into `(key, value)` pairs in file order
+pub fn parse(content: &str) -> Vec<(String, String)> {
+    parse_lines(content)
+        .into_iter()
+        .filter_map(|line| match line {
+            Line::Entry { key, value, .. } => Some((key, value)),
+            Line::Other(_) => None,
+        })
+        .collect()
+}
+
+/// Parse dotenv content into a key/value map; later assignments win
+pub fn to_map(content: &str) -> HashMap<String, String> {
+    parse(content).into_iter().collect()
+}
+
+/// Render a single `KEY=VALUE` line, quoting the value when needed
+pub fn format_entry(key: &str, value: &str) -> String {
+    format!("{}={}", key, format_value(value))
+}
+
+/// Quote a value so `parse_lines` reads it back unchanged
+pub fn format_value(value: &str) -> String {
+    let is_plain = value.chars().all(|c| {
+        c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ':' | '@' | '+' | ',' | '=')
+    });
+    if is_plain {
+        return value.to_string();
+    }
+
+    format!("\"{}\"", escape_double_quoted(value))
+}
+
+/// Escape a value for use between double quotes
+pub fn escape_double_quoted(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Key-level differences between two versions of a dotenv file
+#[derive(Debug, Default)]
+pub struct KeyChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+}
+
+impl KeyChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// One-line summary such as `added: A, B; changed: C`
+    pub fn summary(&self) -> String {
+        let parts: Vec<String> = [
+            ("added", &self.added),
+            ("changed", &self.changed),
+            ("removed", &self.removed),
+        ]
+        .iter()
+        .filter(|(_, keys)| !keys.is_empty())
+        .map(|(label, keys)| format!("{}: {}", label, keys.join(", ")))
+        .collect();
+
+        if parts.is_empty() {
+            "no key changes".to_string()
+        } else {
+            parts.join("; ")
+        }
+    }
+}
+
+/// Compare two dotenv contents key by key, in the order keys appear
+pub fn diff_keys(old: &str, new: &str) -> KeyChanges {
+    let old_entries = parse(old);
+    let new_entries = parse(new);
+    let old_map: HashMap<_, _> = old_entries.iter().cloned().collect();
+    let new_map: HashMap<_, _> = new_entries.iter().cloned().collect();
+
+    let mut changes = KeyChanges::default();
+    for (key, _) in &new_entries {
+        if changes.added.contains(key) || changes.changed.contains(key) {
+            continue;
+        }
+        match old_map.get(key) {
+            None => changes.added.push(key.clone()),
+            Some(old_value) if Some(old_value) != new_map.get(key) => {
+                changes.changed.push(key.clone())
+            }
+            Some(_) => {}
+        }
+    }
+    for (key, _) in &old_entries {
+        if !new_map.contains_key(key) && !changes.removed.contains(key) {
+            changes.removed.push(key.clone());
+        }
+    }
+
+    changes
+}
+
+/// A key changed differently on both sides of a merge
+pub struct Conflict {
+    pub key: String,
+    pub local: Option<String>,
+    pub remote: Option<String>,
+}
+
+/// How a conflicting key is resolved
+pub enum Resolution {
+    Local,
+    Remote,
+    /// Keep both values between conflict markers for manual editing
+    Markers,
+}
+
+/// Outcome of a key-level three-way merge
+pub struct MergeResult {
+    pub content: String,
+    /// Keys whose remote value (or removal) was taken
+    pub from_remote: Vec<String>,
+    pub conflicts: usize,
+    /// Conflicts written as markers instead of being resolved
+    pub unresolved: usize,
+}
+
+enum Decision {
+    Value(Option<String>),
+    Markers {
+        local: Option<String>,
+        remote: Option<String>,
+    },
+}
+
+const MARKER_LOCAL: &str = "<<<<<<< local";
+const MARKER_SEPARATOR: &str = "=======";
+const MARKER_REMOTE: &str = ">>>>>>> vault";
+
+/// Merge local and remote changes made since `base`, key by key.
+///
+/// Keys changed on only one side are merged automatically; keys changed on both
+/// sides are passed to `resolve`. Comments and ordering follow the local file, with
+/// keys that only exist remotely appended at the end.
+pub fn three_way_merge<F>(base: &str, local: &str, remote: &str, mut resolve: F) -> MergeResult
+where
+    F: FnMut(&Conflict) -> Resolution,
+{
+    let base_map = to_map(base);
+    let local_map = to_map(local);
+    let remote_map = to_map(remote);
+    let local_lines = parse_lines(local);
+
+    let mut seen = HashSet::new();
+    let keys: Vec<String> = parse(local)
+        .into_iter()
+        .chain(parse(remote))
+        .map(|(key, _)| key)
+        .filter(|key| seen.insert(key.clone()))
+        .collect();
+
+    let mut result = MergeResult {
+        content: String::new(),
+        from_remote: Vec::new(),
+        conflicts: 0,
+        unresolved: 0,
+    };
+    let mut decisions: HashMap<String, Decision> = HashMap::new();
+
+    for key in &keys {
+        let base_value = base_map.get(key).cloned();
+        let local_value = local_map.get(key).cloned();
+        let remote_value = remote_map.get(key).cloned();
+
+        let decision = if local_value == remote_value || remote_value == base_value {
+            Decision::Value(local_value)
+        } else if local_value == base_value {
+            result.from_remote.push(key.clone());
+            Decision::Value(remote_value)
+        } else {
+            result.conflicts += 1;
+            let conflict = Conflict {
+                key: key.clone(),
+                local: local_value,
+                remote: remote_value,
+            };
+            match resolve(&conflict) {
+                Resolution::Local => Decision::Value(conflict.local),
+                Resolution::Remote => {
+                    result.from_remote.push(key.clone());
+                    Decision::Value(conflict.remote)
+                }
+                Resolution::Markers => {
+                    result.unresolved += 1;
+                    Decision::Markers {
+                        local: conflict.local,
+                        remote: conflict.remote,
+                    }
+                }
+            }
+        };
+        decisions.insert(key.clone(), decision);
+    }
+
+    let mut output: Vec<String> = Vec::new();
+    let mut emitted = HashSet::new();
+    let mut emit = |key: &str, local_line: Option<(&str, &str)>, output: &mut Vec<String>| {
+        if !emitted.insert(key.to_string()) {
+            return;
+        }
+        match decisions.get(key) {
+            Some(Decision::Value(Some(value))) => match local_line {
+                // Keep the original formatting when the local value survives
+                Some((local_value, raw)) if local_value == value => output.push(raw.to_string()),
+                _ => output.push(format_entry(key, value)),
+            },
+            Some(Decision::Markers { local, remote }) => {
+                output.push(MARKER_LOCAL.to_string());
+                output.extend(local.as_deref().map(|value| format_entry(key, value)));
+                output.push(MARKER_SEPARATOR.to_string());
+                output.extend(remote.as_deref().map(|value| format_entry(key, value)));
+                output.push(MARKER_REMOTE.to_string());
+            }
+            Some(Decision::Value(None)) | None => {}
+        }
+    };
+
+    for line in &local_lines {
+        match line {
+            Line::Entry {
+                key, value, raw, ..
+            } => emit(key, Some((value, raw)), &mut output),
+            Line::Other(text) => output.push(text.clone()),
+        }
+    }
+    for key in &keys {
+        emit(key, None, &mut output);
+    }
+
+    result.content = output.join("\n");
+    if !result.content.is_empty() {
+        result.content.push('\n');
+    }
+    result
+}
+
+/// Whether the content still contains unresolved merge conflict markers
+pub fn has_conflict_markers(content: &str) -> bool {
+    content
+        .lines()
+        .any(|line| line == MARKER_LOCAL || line == MARKER_REMOTE)
+}
+
+fn split_assignment(line: &str) -> Option<(String, &str)> {
+    let trimmed = line.trim_start();
+    if trimmed.starts_with('#') {
+        return None;
+    }
+    let trimmed = trimmed.strip_prefix("export ").unwrap_or(trimmed);
+    let (key, value) = trimmed.split_once('=')?;
+    let key = key.trim();
+    let is_valid_key = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+    if !is_valid_key {
+        return None;
+    }
+    Some((key.to_string(), value))
+}
+
+fn find_closing_quote(body: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (idx, c) in body.char_indices() {
+        if quote == '"' && c == '\\' && !escaped {
+            escaped = true;
+            continue;
+        }
+        if c == quote && !escaped {
+            return Some(idx);
+        }
+        escaped = false;
+    }
+    None
+}
+
+fn unescape_double_quoted(inner: &str) -> String {
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('r') => value.push('\r'),
+            Some('t') => value.push('\t'),
+            Some(other) => value.push(other),
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+fn strip_inline_comment(value: &str) -> &str {
+    match value.find(" #") {
+        Some(pos) => &value[..pos],
+        None => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(base: &str, local: &str, remote: &str) -> MergeResult {
+        three_way_merge(base, local, remote, |conflict| {
+            panic!("unexpected conflict on {}", conflict.key)
+        })
+    }
+
+    #[test]
+    fn merges_changes_made_on_one_side() {
+        let result = merge("A=1\nB=1\n", "A=2\nB=1\n", "A=1\nB=3\n");
+
+        assert_eq!(result.content, "A=2\nB=3\n");
+        assert_eq!(result.from_remote, vec!["B"]);
+        assert_eq!(result.conflicts, 0);
+    }
+
+    #[test]
+    fn applies_additions_and_removals_from_either_side() {
+        let result = merge(
+            "A=1\nB=1\nC=1\n",
+            "A=1\nC=1\nLOCAL=1\n",
+            "A=1\nB=1\nREMOTE=1\n",
+        );
+
+        assert_eq!(result.content, "A=1\nLOCAL=1\nREMOTE=1\n");
+        assert_eq!(result.from_remote, vec!["C", "REMOTE"]);
+    }
+
+    #[test]
+    fn keeps_local_comments_and_formatting() {
+        let local = "# Database\nexport DB_HOST=\"localhost\"\n\nDB_PORT=5432\n";
+        let result = merge(
+            "DB_HOST=localhost\nDB_PORT=5432\n",
+            local,
+            "DB_HOST=localhost\nDB_PORT=6543\n",
+        );
+
+        assert_eq!(
+            result.content,
+            "# Database\nexport DB_HOST=\"localhost\"\n\nDB_PORT=6543\n"
+        );
+    }
+
+    #[test]
+    fn identical_changes_do_not_conflict() {
+        let result = merge("A=1\n", "A=2\n", "A=2\n");
+
+        assert_eq!(result.content, "A=2\n");
+        assert!(result.from_remote.is_empty());
+    }
+
+    #[test]
+    fn resolves_conflicts_with_the_chosen_side() {
+        let mut seen = Vec::new();
+        let result = three_way_merge("A=1\nB=1\n", "A=2\nB=2\n", "A=3\nB=3\n", |conflict| {
+            seen.push((
+                conflict.key.clone(),
+                conflict.local.clone(),
+                conflict.remote.clone(),
+            ));
+            if conflict.key == "A" {
+                Resolution::Local
+            } else {
+                Resolution::Remote
+            }
+        });
+
+        assert_eq!(
+            seen,
+            vec![
+                (
+                    "A".to_string(),
+                    Some("2".to_string()),
+                    Some("3".to_string())
+                ),
+                (
+                    "B".to_string(),
+                    Some("2".to_string()),
+                    Some("3".to_string())
+                ),
+            ]
+        );
+        assert_eq!(result.content, "A=2\nB=3\n");
+        assert_eq!(result.conflicts, 2);
+        assert_eq!(result.unresolved, 0);
+        assert_eq!(result.from_remote, vec!["B"]);
+    }
+
+    #[test]
+    fn treats_every_difference_as_a_conflict_without_a_base() {
+        let mut conflicts = 0;
+        let result = three_way_merge("", "A=1\nB=1\n", "A=2\nB=1\n", |_| {
+            conflicts += 1;
+            Resolution::Remote
+        });
+
+        assert_eq!(conflicts, 1);
+        assert_eq!(result.content, "A=2\nB=1\n");
+    }
+
+    #[test]
+    fn writes_markers_for_unresolved_conflicts() {
+        let result = three_way_merge("A=1\n", "A=2\n", "", |_| Resolution::Markers);
+
+        assert_eq!(
+            result.content,
+            "<<<<<<< local\nA=2\n=======\n>>>>>>> vault\n"
+        );
+        assert_eq!(result.unresolved, 1);
+        assert!(has_conflict_markers(&result.content));
+        assert!(!has_conflict_markers("A=1\n# <<<<<<< not a marker\n"));
+    }
+
+    #[test]
+    fn merged_values_survive_a_round_trip() {
+        let remote = "TOKEN=\"a b#c\\\"d\"\nMULTI=\"line1\\nline2\"\n";
+        let result = merge("", "", remote);
+
+        assert_eq!(to_map(&result.content), to_map(remote));
+        assert_eq!(to_map(&result.content)["MULTI"], "line1\nline2");
+    }
+}
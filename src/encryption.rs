@@ -0,0 +1,143 @@
+use crate::fields::{get_fields, new_field, remove_fields, FIELD_TYPE_TEXT};
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Stdio};
+
+// Marks content encrypted with age before it was stored: `passphrase` or `recipients`
+const ENCRYPTION_FIELD_NAME: &str = "bw-env:encryption";
+
+// Comma-separated age recipients, so re-storing can encrypt for the same keys
+const RECIPIENTS_FIELD_NAME: &str = "bw-env:recipients";
+
+// Identity file used to decrypt recipient-encrypted items
+const IDENTITY_ENV_VAR: &str = "BW_ENV_AGE_IDENTITY";
+
+/// Client-side encryption applied on top of the vault's own encryption
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Encryption {
+    None,
+    /// Symmetric encryption with a passphrase prompted by `age`
+    Passphrase,
+    /// Encryption to one or more age/X25519 public keys
+    Recipients(Vec<String>),
+}
+
+/// Client-side encryption used by an existing item
+pub fn encryption_of(item: &Value) -> Encryption {
+    match get_fields(item, ENCRYPTION_FIELD_NAME).first() {
+        Some(&"passphrase") => Encryption::Passphrase,
+        Some(&"recipients") => Encryption::Recipients(
+            get_fields(item, RECIPIENTS_FIELD_NAME)
+                .first()
+                .map(|recipients| {
+                    recipients
+                        .split(',')
+                        .filter(|recipient| !recipient.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        ),
+        _ => Encryption::None,
+    }
+}
+
+/// Whether the item's content is encrypted client-side
+pub fn is_encrypted(item: &Value) -> bool {
+    encryption_of(item) != Encryption::None
+}
+
+/// Custom fields recording how the content was encrypted
+pub fn encryption_fields(encryption: &Encryption) -> Vec<Value> {
+    match encryption {
+        Encryption::None => Vec::new(),
+        Encryption::Passphrase => vec![new_field(
+            ENCRYPTION_FIELD_NAME,
+            "passphrase",
+            FIELD_TYPE_TEXT,
+        )],
+        Encryption::Recipients(recipients) => vec![
+            new_field(ENCRYPTION_FIELD_NAME, "recipients", FIELD_TYPE_TEXT),
+            new_field(RECIPIENTS_FIELD_NAME, &recipients.join(","), FIELD_TYPE_TEXT),
+        ],
+    }
+}
+
+/// Remove the fields recording client-side encryption
+pub fn clear_encryption_fields(item: &mut Value) {
+    remove_fields(item, ENCRYPTION_FIELD_NAME);
+    remove_fields(item, RECIPIENTS_FIELD_NAME);
+}
+
+/// Encrypt content with age, returning ASCII-armored ciphertext
+pub fn encrypt(content: &str, encryption: &Encryption) -> Result<String> {
+    let mut args = vec!["--encrypt".to_string(), "--armor".to_string()];
+    match encryption {
+        Encryption::None => return Ok(content.to_string()),
+        Encryption::Passphrase => {
+            println!("Encrypting with a passphrase before storing...");
+            args.push("--passphrase".to_string());
+        }
+        Encryption::Recipients(recipients) => {
+            println!(
+                "Encrypting for {} recipient(s) before storing...",
+                recipients.len()
+            );
+            for recipient in recipients {
+                args.push("--recipient".to_string());
+                args.push(recipient.clone());
+            }
+        }
+    }
+
+    let output = run_age(&args, content.as_bytes())?;
+    String::from_utf8(output).context("Failed to parse age output")
+}
+
+/// Decrypt ASCII-armored age ciphertext.
+///
+/// Passphrase-encrypted content is prompted for by `age`; recipient-encrypted content
+/// uses the identity file from `BW_ENV_AGE_IDENTITY`.
+pub fn decrypt(armored: &str) -> Result<String> {
+    let mut args = vec!["--decrypt".to_string()];
+    if let Some(identity) = std::env::var_os(IDENTITY_ENV_VAR) {
+        args.push("--identity".to_string());
+        args.push(identity.to_string_lossy().to_string());
+    }
+
+    let output = run_age(&args, armored.as_bytes())?;
+    String::from_utf8(output).context("Decrypted content is not valid UTF-8")
+}
+
+fn run_age(args: &[String], input: &[u8]) -> Result<Vec<u8>> {
+    // age prompts for passphrases on the terminal, so only stdin/stdout are piped
+    let mut child = match Command::new("age")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) if err.kind() == ErrorKind::NotFound => anyhow::bail!(
+            "'age' is not installed or not on your PATH. Install it from https://age-encryption.org."
+        ),
+        Err(err) => return Err(err).context("Failed to run age"),
+    };
+
+    // Write from a separate thread so large inputs cannot deadlock against age's output
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+
+    let output = child.wait_with_output().context("Failed to wait for age")?;
+    let written = writer.join();
+    if !output.status.success() {
+        anyhow::bail!("age failed to process the content");
+    }
+    written
+        .map_err(|_| anyhow::anyhow!("Failed to write to age stdin"))?
+        .context("Failed to write to age stdin")?;
+    Ok(output.stdout)
+}
@@ -0,0 +1,52 @@
+use serde_json::Value;
+
+/// Bitwarden custom field type for hidden values
+pub const FIELD_TYPE_HIDDEN: u64 = 1;
+/// Bitwarden custom field type for plain text values
+pub const FIELD_TYPE_TEXT: u64 = 0;
+
+/// Prefix for the custom fields bw-env manages on its items
+pub const FIELD_PREFIX: &str = "bw-env:";
+
+/// Return the values of all custom fields with the given name, in order
+pub fn get_fields<'a>(item: &'a Value, name: &str) -> Vec<&'a str> {
+    item["fields"]
+        .as_array()
+        .map(|fields| {
+            fields
+                .iter()
+                .filter(|field| field["name"].as_str() == Some(name))
+                .filter_map(|field| field["value"].as_str())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Build a custom field object
+pub fn new_field(name: &str, value: &str, field_type: u64) -> Value {
+    serde_json::json!({
+        "name": name,
+        "value": value,
+        "type": field_type,
+        "linkedId": null,
+    })
+}
+
+/// Set a custom field, replacing any existing fields with the same name
+pub fn set_field(item: &mut Value, name: &str, value: &str, field_type: u64) {
+    remove_fields(item, name);
+    fields_mut(item).push(new_field(name, value, field_type));
+}
+
+/// Remove every custom field with the given name
+pub fn remove_fields(item: &mut Value, name: &str) {
+    fields_mut(item).retain(|field| field["name"].as_str() != Some(name));
+}
+
+/// Mutable access to the item's `fields` array, creating it when missing
+pub fn fields_mut(item: &mut Value) -> &mut Vec<Value> {
+    if !item["fields"].is_array() {
+        item["fields"] = Value::Array(Vec::new());
+    }
+    item["fields"].as_array_mut().unwrap()
+}
@@ -0,0 +1,42 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Trimmed output of a git command run in `dir`, or `None` when git fails or prints nothing
+pub fn git_output(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    Some(value).filter(|value| !value.is_empty())
+}
+
+/// Root of the git repository containing `dir`
+pub fn repo_root(dir: &Path) -> Option<PathBuf> {
+    git_output(dir, &["rev-parse", "--show-toplevel"]).map(PathBuf::from)
+}
+
+/// Path of a file relative to the root of its git repository, with `/` separators
+pub fn repo_relative_path(path: &Path) -> Option<String> {
+    let root = fs_canonicalize(&repo_root(parent_dir(path))?)?;
+    let absolute = fs_canonicalize(path)?;
+    absolute
+        .strip_prefix(root)
+        .ok()
+        .map(|relative| relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Directory containing `path`, treating a bare file name as the current directory
+pub fn parent_dir(path: &Path) -> &Path {
+    path.parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+}
+
+fn fs_canonicalize(path: &Path) -> Option<PathBuf> {
+    std::fs::canonicalize(path).ok()
+}
@@ -2,23 +2,113 @@ mod auth;
 mod commands;
 mod cli;
 mod bw_commands;
+mod chunks;
+mod content;
+mod dotenv;
+mod encryption;
+mod fields;
+mod git;
+mod naming;
+mod picker;
+mod provenance;
+mod secure_file;
+mod session;
+mod state;
+mod vault;
+#[cfg(all(test, unix))]
+mod test_support;
 
 use clap::Parser;
 use anyhow::Result;
 use cli::{Cli, Commands};
-use auth::unlock_vault;
-use commands::{store_env, retrieve_env, list_env_items};
+use auth::{unlock_vault, PasswordSource};
+use commands::list::ListOptions;
+use encryption::Encryption;
+use commands::{store_env, retrieve_env, Overwrite, list_env_items, history_env, rollback_env, diff_env, merge_env, run_env, export_env, direnv_env, open_env, unlock_env, lock_env};
 
-fn main() -> Result<()> {
+fn main() {
     let cli = Cli::parse();
+    // Like diff(1), `diff` exits 1 when the files differ and 2 when it failed
+    let error_code = if matches!(cli.command, Commands::Diff { .. }) { 2 } else { 1 };
+
+    if let Err(err) = run(cli) {
+        eprintln!("Error: {:?}", err);
+        std::process::exit(error_code);
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let password = match (cli.passwordenv, cli.passwordfile) {
+        (Some(var), _) => Some(PasswordSource::Env(var)),
+        (None, Some(path)) => Some(PasswordSource::File(path)),
+        (None, None) => None,
+    };
     
-    // Unlock the vault first to avoid multiple password prompts
-    unlock_vault()?;
+    // Unlock the vault first to avoid multiple password prompts; direnv runs on every `cd`,
+    // so it only unlocks when its cache is stale, and unlock/lock manage the session themselves
+    if !matches!(
+        cli.command,
+        Commands::Direnv { .. } | Commands::Unlock { .. } | Commands::Lock
+    ) {
+        unlock_vault(password.as_ref())?;
+    }
     
     match cli.command {
-        Commands::Store { path } => store_env(&path)?,
-        Commands::Retrieve => retrieve_env()?,
-        Commands::List => list_env_items()?,
+        Commands::Store { path, layout, encrypt, recipients, no_encrypt } => {
+            let encryption = if !recipients.is_empty() {
+                Some(Encryption::Recipients(recipients))
+            } else if encrypt {
+                Some(Encryption::Passphrase)
+            } else if no_encrypt {
+                Some(Encryption::None)
+            } else {
+                None
+            };
+            store_env(&path, layout, encryption)?
+        }
+        Commands::Retrieve { item, output, flat, force, backup, skip, filters } => {
+            let overwrite = if force {
+                Overwrite::Force
+            } else if backup {
+                Overwrite::Backup
+            } else if skip {
+                Overwrite::Skip
+            } else {
+                Overwrite::Ask
+            };
+            retrieve_env(item.as_deref(), output.as_deref(), flat, overwrite, &filters)?
+        }
+        Commands::List { format, repo, all_repos, name, since, folder, sort, tree, filters } => {
+            list_env_items(&ListOptions {
+                format,
+                filters,
+                repo,
+                all_repos,
+                name,
+                since,
+                folder,
+                sort,
+                tree,
+            })?
+        }
+        Commands::Diff { path, item, reveal } => {
+            if diff_env(&path, item.as_deref(), reveal)? {
+                std::process::exit(1);
+            }
+        }
+        Commands::Merge { path, item, markers } => merge_env(&path, item.as_deref(), markers)?,
+        Commands::History { item } => history_env(&item)?,
+        Commands::Rollback { item, to } => rollback_env(&item, to)?,
+        Commands::Direnv { item, ttl, refresh, stdlib } => {
+            direnv_env(item.as_deref(), ttl, refresh, stdlib, password.as_ref())?
+        }
+        Commands::Export { item, format } => export_env(item.as_deref(), format)?,
+        Commands::Open { item } => open_env(item.as_deref())?,
+        Commands::Unlock { ttl } => unlock_env(ttl, password.as_ref())?,
+        Commands::Lock => lock_env()?,
+        Commands::Run { item, no_override, clean, command } => {
+            run_env(&command, item.as_deref(), no_override, clean)?
+        }
     }
     Ok(())
 }
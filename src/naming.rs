@@ -0,0 +1,137 @@
+use std::collections::{BTreeMap, HashMap};
+use std::process::Command;
+
+// Item names encode where a file came from: `owner/repo/path/in/repo/file`
+
+/// `owner/repo` of the current directory's GitHub remote, the prefix of its item names
+pub fn current_context_dir() -> Option<String> {
+    // Infer org/repo from git remote
+    let output = Command::new("git")
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let remote_url = String::from_utf8(output.stdout).ok()?;
+    let remote_url = remote_url.trim();
+
+    let repo_info = if remote_url.starts_with("https://github.com/") {
+        remote_url
+            .strip_prefix("https://github.com/")?
+            .strip_suffix(".git")
+            .unwrap_or(remote_url.strip_prefix("https://github.com/")?)
+            .to_string()
+    } else if remote_url.starts_with("git@github.com:") {
+        remote_url
+            .strip_prefix("git@github.com:")?
+            .strip_suffix(".git")
+            .unwrap_or(remote_url.strip_prefix("git@github.com:")?)
+            .to_string()
+    } else {
+        return None;
+    };
+
+    let parts: Vec<&str> = repo_info.split('/').collect();
+    if parts.len() == 2 {
+        Some(format!("{}/{}", parts[0], parts[1]))
+    } else {
+        None
+    }
+}
+
+/// Directory part of an item name, if any
+pub fn item_dir(name: &str) -> Option<String> {
+    name.rfind('/')
+        .map(|pos| name[..pos].to_string())
+        .filter(|dir| !dir.is_empty())
+}
+
+/// Last segment of an item name: the stored file's name
+pub fn item_file_name(name: &str) -> &str {
+    name.rsplit(['/', '\\']).next().unwrap_or(name)
+}
+
+/// Indices of the items in each directory
+pub fn group_items_by_dir(items: &[serde_json::Value]) -> HashMap<String, Vec<usize>> {
+    items
+        .iter()
+        .enumerate()
+        .fold(HashMap::new(), |mut acc, (idx, item)| {
+            if let Some(dir) = item["name"].as_str().and_then(item_dir) {
+                acc.entry(dir).or_default().push(idx);
+            }
+            acc
+        })
+}
+
+/// A directory in the hierarchy of item names: owner, then repository, then subdirectories
+#[derive(Default)]
+pub struct DirNode {
+    /// Indices of the items directly in this directory
+    pub items: Vec<usize>,
+    pub children: BTreeMap<String, DirNode>,
+}
+
+impl DirNode {
+    /// Number of items in this directory and all directories below it
+    pub fn count(&self) -> usize {
+        self.items.len() + self.children.values().map(DirNode::count).sum::<usize>()
+    }
+}
+
+/// Nest the items by the directories in their names; items without a directory stay at the root
+pub fn build_tree(items: &[serde_json::Value]) -> DirNode {
+    let mut root = DirNode::default();
+    let mut groups: Vec<(String, Vec<usize>)> = group_items_by_dir(items).into_iter().collect();
+    // Keep items in their listed order within each directory
+    groups.iter_mut().for_each(|(_, indices)| indices.sort());
+
+    for (dir, indices) in groups {
+        let node = dir
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .fold(&mut root, |node, segment| {
+                node.children.entry(segment.to_string()).or_default()
+            });
+        node.items.extend(indices);
+    }
+
+    root.items = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item["name"].as_str().and_then(item_dir).is_none())
+        .map(|(idx, _)| idx)
+        .collect();
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_nested_directories_with_counts() {
+        let items: Vec<serde_json::Value> = [
+            "acme/api/.env",
+            "acme/api/config/.env",
+            "acme/web/.env",
+            "loose.env",
+        ]
+        .iter()
+        .map(|name| serde_json::json!({ "name": name }))
+        .collect();
+
+        let root = build_tree(&items);
+
+        assert_eq!(root.items, [3]);
+        assert_eq!(root.count(), 4);
+        let acme = &root.children["acme"];
+        assert!(acme.items.is_empty());
+        assert_eq!(acme.children.keys().collect::<Vec<_>>(), ["api", "web"]);
+        assert_eq!(acme.children["api"].items, [0]);
+        assert_eq!(acme.children["api"].children["config"].items, [1]);
+        assert_eq!(acme.children["api"].count(), 2);
+        assert_eq!(acme.children["web"].items, [2]);
+    }
+}
@@ -0,0 +1,301 @@
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, queue};
+use std::collections::BTreeSet;
+use std::io::{self, IsTerminal, Write};
+
+/// A choice in the picker
+pub struct Entry {
+    /// Header the entry is listed under; entries with the same group should be adjacent
+    pub group: String,
+    pub label: String,
+    /// Lines shown in the preview pane while the entry is highlighted
+    pub preview: Vec<String>,
+}
+
+/// Whether the full-screen picker can be used, i.e. both stdin and stdout are terminals
+pub fn is_interactive() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+/// Full-screen fuzzy finder over `entries`.
+///
+/// Typing filters the list, arrows move, Enter picks the highlighted entry and Esc cancels.
+/// With `multi`, Tab marks entries and Enter picks every marked one. Returns the indices of
+/// the picked entries, or `None` when cancelled.
+pub fn pick(prompt: &str, entries: &[Entry], multi: bool) -> Result<Option<Vec<usize>>> {
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode().context("Failed to set up the terminal")?;
+    let result = queue!(stdout, terminal::EnterAlternateScreen, cursor::Hide)
+        .and_then(|_| stdout.flush())
+        .context("Failed to set up the terminal")
+        .and_then(|_| run(&mut stdout, prompt, entries, multi));
+
+    // Always give the terminal back, even when drawing failed
+    let _ = queue!(stdout, cursor::Show, terminal::LeaveAlternateScreen);
+    let _ = stdout.flush();
+    let _ = terminal::disable_raw_mode();
+    result
+}
+
+struct State {
+    query: String,
+    /// Indices of the entries matching the query, in display order
+    matches: Vec<usize>,
+    /// Position of the highlighted entry within `matches`
+    cursor: usize,
+    /// First line of the list that is visible
+    scroll: usize,
+    marked: BTreeSet<usize>,
+}
+
+fn run(
+    stdout: &mut io::Stdout,
+    prompt: &str,
+    entries: &[Entry],
+    multi: bool,
+) -> Result<Option<Vec<usize>>> {
+    let mut state = State {
+        query: String::new(),
+        matches: filter(entries, ""),
+        cursor: 0,
+        scroll: 0,
+        marked: BTreeSet::new(),
+    };
+
+    loop {
+        draw(stdout, prompt, entries, &mut state, multi)?;
+
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind,
+            ..
+        }) = event::read().context("Failed to read terminal input")?
+        else {
+            continue;
+        };
+        if kind == KeyEventKind::Release {
+            continue;
+        }
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+
+        match code {
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Char('c') if ctrl => return Ok(None),
+            KeyCode::Enter => {
+                if multi && !state.marked.is_empty() {
+                    return Ok(Some(state.marked.into_iter().collect()));
+                }
+                if let Some(&idx) = state.matches.get(state.cursor) {
+                    return Ok(Some(vec![idx]));
+                }
+            }
+            KeyCode::Tab if multi => {
+                if let Some(&idx) = state.matches.get(state.cursor) {
+                    if !state.marked.remove(&idx) {
+                        state.marked.insert(idx);
+                    }
+                    state.cursor = (state.cursor + 1).min(state.matches.len().saturating_sub(1));
+                }
+            }
+            KeyCode::Up => state.cursor = state.cursor.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => state.cursor = state.cursor.saturating_sub(1),
+            KeyCode::Down => {
+                state.cursor = (state.cursor + 1).min(state.matches.len().saturating_sub(1))
+            }
+            KeyCode::Char('n') if ctrl => {
+                state.cursor = (state.cursor + 1).min(state.matches.len().saturating_sub(1))
+            }
+            KeyCode::Backspace => {
+                state.query.pop();
+                state.matches = filter(entries, &state.query);
+                state.cursor = 0;
+            }
+            KeyCode::Char('u') if ctrl => {
+                state.query.clear();
+                state.matches = filter(entries, &state.query);
+                state.cursor = 0;
+            }
+            KeyCode::Char(c) if !ctrl => {
+                state.query.push(c);
+                state.matches = filter(entries, &state.query);
+                state.cursor = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A row of the list: a group header or an entry
+enum Row {
+    Header(usize),
+    Entry(usize),
+}
+
+fn draw(
+    stdout: &mut io::Stdout,
+    prompt: &str,
+    entries: &[Entry],
+    state: &mut State,
+    multi: bool,
+) -> Result<()> {
+    let (width, height) = terminal::size().context("Failed to read the terminal size")?;
+    let (width, height) = (width as usize, height as usize);
+    let list_width = if width >= 80 { width * 3 / 5 } else { width };
+    let list_height = height.saturating_sub(3);
+
+    // Headers are repeated whenever the group changes among the matches
+    let mut rows = Vec::new();
+    let mut cursor_row = 0;
+    for (position, &idx) in state.matches.iter().enumerate() {
+        let group_changed = position == 0
+            || entries[state.matches[position - 1]].group != entries[idx].group;
+        if group_changed && !entries[idx].group.is_empty() {
+            rows.push(Row::Header(idx));
+        }
+        if position == state.cursor {
+            cursor_row = rows.len();
+        }
+        rows.push(Row::Entry(idx));
+    }
+
+    // Keep the highlighted entry, and its header when possible, on screen
+    if cursor_row < state.scroll + 1 {
+        state.scroll = cursor_row.saturating_sub(1);
+    } else if cursor_row >= state.scroll + list_height {
+        state.scroll = cursor_row + 1 - list_height;
+    }
+
+    queue!(stdout, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+    queue!(
+        stdout,
+        SetAttribute(Attribute::Bold),
+        Print(truncate(&format!("{}> {}", prompt, state.query), width)),
+        SetAttribute(Attribute::Reset),
+        cursor::MoveTo(0, 1),
+    )?;
+    let help = if multi {
+        "type to filter · ↑/↓ move · Tab mark · Enter pick · Esc cancel"
+    } else {
+        "type to filter · ↑/↓ move · Enter pick · Esc cancel"
+    };
+    let status = match state.marked.len() {
+        0 => format!("{}/{} · {}", state.matches.len(), entries.len(), help),
+        marked => format!(
+            "{}/{} · {} marked · {}",
+            state.matches.len(),
+            entries.len(),
+            marked,
+            help
+        ),
+    };
+    queue!(
+        stdout,
+        SetAttribute(Attribute::Dim),
+        Print(truncate(&status, width)),
+        SetAttribute(Attribute::Reset),
+    )?;
+
+    for (line, row) in rows.iter().skip(state.scroll).take(list_height).enumerate() {
+        queue!(stdout, cursor::MoveTo(0, (line + 3) as u16))?;
+        match row {
+            Row::Header(idx) => queue!(
+                stdout,
+                SetAttribute(Attribute::Bold),
+                Print(truncate(&entries[*idx].group, list_width)),
+                SetAttribute(Attribute::Reset),
+            )?,
+            Row::Entry(idx) => {
+                let highlighted = state.matches.get(state.cursor) == Some(idx);
+                let mark = if state.marked.contains(idx) { "●" } else { " " };
+                let pointer = if highlighted { ">" } else { " " };
+                let text = format!("{}{} {}", pointer, mark, entries[*idx].label);
+                if highlighted {
+                    queue!(stdout, SetAttribute(Attribute::Reverse))?;
+                }
+                queue!(
+                    stdout,
+                    Print(truncate(&text, list_width.saturating_sub(1))),
+                    SetAttribute(Attribute::Reset),
+                )?;
+            }
+        }
+    }
+
+    // Preview pane on wide terminals
+    if list_width < width {
+        if let Some(&idx) = state.matches.get(state.cursor) {
+            let preview_width = width - list_width - 2;
+            for (line, text) in entries[idx].preview.iter().take(list_height).enumerate() {
+                queue!(
+                    stdout,
+                    cursor::MoveTo(list_width as u16, (line + 3) as u16),
+                    Print("│ "),
+                    Print(truncate(text, preview_width)),
+                )?;
+            }
+        }
+    }
+
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Entries matching the query, best matches first within each group
+fn filter(entries: &[Entry], query: &str) -> Vec<usize> {
+    let mut scored: Vec<(usize, i64)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, entry)| fuzzy_score(query, &entry.label).map(|score| (idx, score)))
+        .collect();
+
+    // Keep groups in their original order so headers stay together
+    let group_order = |idx: usize| {
+        entries
+            .iter()
+            .position(|entry| entry.group == entries[idx].group)
+            .unwrap_or(idx)
+    };
+    scored.sort_by_key(|&(idx, score)| (group_order(idx), -score));
+    scored.into_iter().map(|(idx, _)| idx).collect()
+}
+
+/// Case-insensitive subsequence match, scoring consecutive characters and matches at the
+/// start of a path segment or word higher. `None` when the query does not match.
+fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text: Vec<char> = text.chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let wanted = wanted.to_lowercase().next().unwrap_or(wanted);
+        let found = (position..text.len())
+            .find(|&idx| text[idx].to_lowercase().next() == Some(wanted))?;
+
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || matches!(text[found - 1], '/' | '\\' | '.' | '-' | '_' | ' ') {
+            score += 3;
+        }
+        previous = Some(found);
+        position = found + 1;
+    }
+
+    // Prefer shorter labels among equally good matches
+    Some(score * 100 - text.len() as i64)
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
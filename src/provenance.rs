@@ -0,0 +1,188 @@
+use crate::bw_commands::get_status;
+use crate::chunks::sha256_hex;
+use crate::encryption::Encryption;
+use crate::fields::{
+    fields_mut, get_fields, new_field, remove_fields, set_field, FIELD_PREFIX, FIELD_TYPE_TEXT,
+};
+use crate::git::{git_output, parent_dir, repo_relative_path};
+use serde_json::Value;
+use std::path::Path;
+use std::process::Command;
+
+// Custom fields recording where an item was stored from, with their short filter keys
+const SOURCE_PATH_FIELD_NAME: &str = "bw-env:source-path";
+const REMOTE_FIELD_NAME: &str = "bw-env:remote";
+const BRANCH_FIELD_NAME: &str = "bw-env:branch";
+const COMMIT_FIELD_NAME: &str = "bw-env:commit";
+const HOSTNAME_FIELD_NAME: &str = "bw-env:hostname";
+const STORED_BY_FIELD_NAME: &str = "bw-env:stored-by";
+const SHA256_FIELD_NAME: &str = "bw-env:content-sha256";
+
+const PROVENANCE_FIELD_NAMES: [&str; 7] = [
+    SOURCE_PATH_FIELD_NAME,
+    REMOTE_FIELD_NAME,
+    BRANCH_FIELD_NAME,
+    COMMIT_FIELD_NAME,
+    HOSTNAME_FIELD_NAME,
+    STORED_BY_FIELD_NAME,
+    SHA256_FIELD_NAME,
+];
+
+/// Collect provenance fields for a file about to be stored
+pub fn collect_provenance(path: &str, content: &[u8]) -> Vec<Value> {
+    let file_path = Path::new(path);
+    let dir = parent_dir(file_path);
+    let source_path = repo_relative_path(file_path).unwrap_or_else(|| path.to_string());
+
+    let values = [
+        (SOURCE_PATH_FIELD_NAME, Some(source_path)),
+        (REMOTE_FIELD_NAME, git_output(dir, &["remote", "get-url", "origin"])),
+        (BRANCH_FIELD_NAME, git_output(dir, &["rev-parse", "--abbrev-ref", "HEAD"])),
+        (COMMIT_FIELD_NAME, git_output(dir, &["rev-parse", "HEAD"])),
+        (HOSTNAME_FIELD_NAME, hostname()),
+        (STORED_BY_FIELD_NAME, user_email()),
+        (SHA256_FIELD_NAME, Some(sha256_hex(content))),
+    ];
+
+    values
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| new_field(name, &value, FIELD_TYPE_TEXT)))
+        .collect()
+}
+
+/// Replace the item's provenance fields with freshly collected ones
+pub fn apply_provenance(item: &mut Value, provenance: &[Value]) {
+    for name in PROVENANCE_FIELD_NAMES {
+        remove_fields(item, name);
+    }
+    fields_mut(item).extend(provenance.iter().cloned());
+}
+
+/// Record the checksum of the item's (plaintext) content.
+///
+/// Client-side encrypted items get none: anyone with vault access could use a plaintext
+/// hash to confirm guesses of the content.
+pub fn set_checksum(item: &mut Value, content: &[u8], encryption: &Encryption) {
+    if *encryption == Encryption::None {
+        set_field(item, SHA256_FIELD_NAME, &sha256_hex(content), FIELD_TYPE_TEXT);
+    } else {
+        remove_fields(item, SHA256_FIELD_NAME);
+    }
+}
+
+/// Drop the content checksum from collected provenance, for client-side encrypted content
+pub fn without_checksum(provenance: &[Value]) -> Vec<Value> {
+    provenance
+        .iter()
+        .filter(|field| field["name"].as_str() != Some(SHA256_FIELD_NAME))
+        .cloned()
+        .collect()
+}
+
+/// SHA-256 of the item's (plaintext) content, when it was recorded
+pub fn content_checksum(item: &Value) -> Option<&str> {
+    get_fields(item, SHA256_FIELD_NAME).first().copied()
+}
+
+/// Provenance of an item as `(key, value)` pairs, using the short filter keys
+pub fn provenance_of(item: &Value) -> Vec<(&'static str, &str)> {
+    PROVENANCE_FIELD_NAMES
+        .iter()
+        .filter_map(|name| {
+            let value = get_fields(item, name).first().copied()?;
+            Some((short_key(name), value))
+        })
+        .collect()
+}
+
+/// Short keys of all provenance fields, as used by `provenance_of` and `--where`
+pub fn provenance_keys() -> Vec<&'static str> {
+    PROVENANCE_FIELD_NAMES
+        .iter()
+        .map(|name| short_key(name))
+        .collect()
+}
+
+/// One-line description of where an item was stored from, if known
+pub fn describe_provenance(item: &Value) -> Option<String> {
+    let get = |name: &str| get_fields(item, name).first().copied();
+    let source = get(SOURCE_PATH_FIELD_NAME)?;
+
+    let mut description = format!("from {}", source);
+    if let Some(branch) = get(BRANCH_FIELD_NAME) {
+        description.push_str(&format!(" on {}", branch));
+    }
+    if let Some(commit) = get(COMMIT_FIELD_NAME) {
+        // Fields can be edited in the Bitwarden UI, so do not assume ASCII
+        let short: String = commit.chars().take(7).collect();
+        description.push_str(&format!(" @ {}", short));
+    }
+    if let Some(hostname) = get(HOSTNAME_FIELD_NAME) {
+        description.push_str(&format!(", host {}", hostname));
+    }
+    if let Some(stored_by) = get(STORED_BY_FIELD_NAME) {
+        description.push_str(&format!(", by {}", stored_by));
+    }
+    Some(description)
+}
+
+/// Whether the item matches every `KEY=VALUE` provenance filter.
+///
+/// Values must match exactly, except `commit` and `content-sha256`, which also match by prefix.
+pub fn matches_filters(item: &Value, filters: &[String]) -> bool {
+    let provenance = provenance_of(item);
+    filters.iter().all(|filter| {
+        let Some((key, expected)) = filter.split_once('=') else {
+            return false;
+        };
+        provenance.iter().any(|(name, value)| {
+            *name == key
+                && (*value == expected
+                    || (matches!(key, "commit" | "content-sha256") && value.starts_with(expected)))
+        })
+    })
+}
+
+/// Validate `KEY=VALUE` provenance filters before using them
+pub fn validate_filters(filters: &[String]) -> anyhow::Result<()> {
+    for filter in filters {
+        let key = filter.split_once('=').map(|(key, _)| key);
+        let known = key.is_some_and(|key| {
+            PROVENANCE_FIELD_NAMES
+                .iter()
+                .any(|name| short_key(name) == key)
+        });
+        if !known {
+            anyhow::bail!(
+                "Invalid filter '{}'; expected KEY=VALUE with KEY one of: {}",
+                filter,
+                provenance_keys().join(", ")
+            );
+        }
+    }
+    Ok(())
+}
+
+fn short_key(field_name: &'static str) -> &'static str {
+    field_name.trim_start_matches(FIELD_PREFIX)
+}
+
+fn hostname() -> Option<String> {
+    Command::new("hostname")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+}
+
+fn user_email() -> Option<String> {
+    get_status()
+        .ok()?
+        .get("userEmail")?
+        .as_str()
+        .map(str::to_string)
+}
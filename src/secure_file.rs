@@ -0,0 +1,89 @@
+use crate::git::parent_dir;
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+/// Write secret content to `path` atomically.
+///
+/// The content goes to a temp file in the same directory, created with mode 0600 (or the
+/// mode of the file being replaced), which is synced to disk and renamed into place, so a
+/// crash never leaves a truncated or world-readable file behind.
+pub fn write_secret_file(path: &Path, content: &[u8]) -> Result<()> {
+    let dir = parent_dir(path);
+    warn_if_shared_dir(dir);
+
+    let existing_permissions = fs::metadata(path).ok().map(|metadata| metadata.permissions());
+    let temp_path = temp_path_for(path);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+
+    let written = options.open(&temp_path).and_then(|mut file| {
+        file.write_all(content)?;
+        if let Some(permissions) = existing_permissions {
+            file.set_permissions(permissions)?;
+        }
+        file.sync_all()
+    });
+    let renamed = written.and_then(|_| fs::rename(&temp_path, path));
+    if let Err(err) = renamed {
+        let _ = fs::remove_file(&temp_path);
+        return Err(err).with_context(|| format!("Failed to write {:?}", path));
+    }
+
+    // Persist the rename itself; not all platforms can open directories, so this is best effort
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// bw-env's directory under the per-user runtime directory, for short-lived secrets.
+///
+/// Only `XDG_RUNTIME_DIR` (or the per-user `TMPDIR` on macOS) is used, which is private to
+/// the user and cleared on logout or reboot.
+pub fn runtime_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .or_else(|| std::env::var_os("TMPDIR").filter(|_| cfg!(target_os = "macos")))
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .map(|dir| dir.join("bw-env"))
+}
+
+/// Create a directory (and its parents) readable only by the user
+pub fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(dir)
+}
+
+fn temp_path_for(path: &Path) -> PathBuf {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".bw-env-{}.tmp", std::process::id()));
+    path.with_file_name(name)
+}
+
+#[cfg(unix)]
+fn warn_if_shared_dir(dir: &Path) {
+    let Ok(metadata) = fs::metadata(dir) else {
+        return;
+    };
+    if metadata.permissions().mode() & 0o022 != 0 {
+        eprintln!(
+            "Warning: {:?} is writable by other users, who could replace or tamper with files in it",
+            dir
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_if_shared_dir(_dir: &Path) {}
@@ -0,0 +1,61 @@
+use crate::secure_file::{create_private_dir, runtime_dir, write_secret_file};
+use anyhow::{anyhow, Context, Result};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Session key cached by `bw-env unlock`, with the time it stops being reused
+const SESSION_FILE_NAME: &str = "session.json";
+
+/// The cached session key, unless there is none or it has expired
+pub fn load_session() -> Option<String> {
+    let path = session_path()?;
+    let bytes = fs::read(&path).ok()?;
+    let cache = serde_json::from_slice::<serde_json::Value>(&bytes).ok()?;
+
+    if cache["expiresAt"].as_u64().unwrap_or(0) <= now() {
+        let _ = fs::remove_file(&path);
+        return None;
+    }
+    cache["session"].as_str().map(str::to_string)
+}
+
+/// Cache a session key for `ttl` seconds, in a file readable only by the user
+pub fn save_session(session: &str, ttl: u64) -> Result<()> {
+    let path = session_path().ok_or_else(|| {
+        anyhow!("No private runtime directory to cache the session in; set XDG_RUNTIME_DIR")
+    })?;
+    if let Some(dir) = path.parent() {
+        create_private_dir(dir).with_context(|| format!("Failed to create directory {:?}", dir))?;
+    }
+
+    let cache = serde_json::json!({
+        "session": session,
+        "expiresAt": now().saturating_add(ttl),
+    });
+    write_secret_file(&path, cache.to_string().as_bytes())
+        .with_context(|| format!("Failed to cache the session in {:?}", path))
+}
+
+/// Remove the cached session key; returns whether there was one
+pub fn forget_session() -> Result<bool> {
+    let Some(path) = session_path() else {
+        return Ok(false);
+    };
+    match fs::remove_file(&path) {
+        Ok(()) => Ok(true),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err).with_context(|| format!("Failed to remove {:?}", path)),
+    }
+}
+
+fn session_path() -> Option<PathBuf> {
+    Some(runtime_dir()?.join(SESSION_FILE_NAME))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0)
+}
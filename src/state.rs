@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Local bookkeeping of which item revision each file was last synced with.
+// Only ids and revision dates are stored here, never secret values.
+const STATE_FILE_NAME: &str = "state.json";
+
+/// Remember that the local file at `path` now matches the given item revision
+pub fn record_sync(path: &Path, item: &serde_json::Value) {
+    let (Some(item_id), Some(revision_date)) =
+        (item["id"].as_str(), item["revisionDate"].as_str())
+    else {
+        return;
+    };
+    let Some(state_path) = state_file_path() else {
+        return;
+    };
+
+    let mut state = load_state(&state_path);
+    state["files"][file_key(path)] = serde_json::json!({
+        "itemId": item_id,
+        "revisionDate": revision_date,
+    });
+
+    let written = state_path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&state_path, state.to_string()));
+    if let Err(err) = written {
+        eprintln!(
+            "Warning: failed to record sync state in {:?}: {}",
+            state_path, err
+        );
+    }
+}
+
+/// Revision date of `item_id` that the local file at `path` was last synced with
+pub fn synced_revision(path: &Path, item_id: &str) -> Option<String> {
+    let state = load_state(&state_file_path()?);
+    let entry = &state["files"][file_key(path)];
+    if entry["itemId"].as_str() != Some(item_id) {
+        return None;
+    }
+    entry["revisionDate"].as_str().map(str::to_string)
+}
+
+fn load_state(state_path: &Path) -> serde_json::Value {
+    fs::read(state_path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice::<serde_json::Value>(&bytes).ok())
+        .filter(|state| state["files"].is_object())
+        .unwrap_or_else(|| serde_json::json!({ "files": {} }))
+}
+
+fn file_key(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+fn state_file_path() -> Option<PathBuf> {
+    let state_home = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state")))?;
+    Some(state_home.join("bw-env").join(STATE_FILE_NAME))
+}
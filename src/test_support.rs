@@ -0,0 +1,138 @@
+//! A fake `bw` executable for tests that go through the Bitwarden CLI
+
+use serde_json::Value;
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+// Items are kept as `items/<id>.json`; every call is logged to `calls` as "<command> <object>";
+// a `fail-<command>-<object>` file makes that call fail with a message on stderr, once the
+// number of calls it holds (if any) succeeded
+const SCRIPT: &str = r#"#!/bin/sh
+dir=$(dirname "$0")
+printf '%s %s\n' "$1" "$2" >> "$dir/calls"
+fail="$dir/fail-$1-$2"
+if [ -e "$fail" ]; then
+    left=$(cat "$fail")
+    if [ -z "$left" ] || [ "$left" -le 0 ]; then
+        echo "fake failure: $1 $2" >&2
+        exit 1
+    fi
+    echo $((left - 1)) > "$fail"
+fi
+case "$1 $2" in
+    "get template") echo '{}' ;;
+    "encode "*) base64 | tr -d '\n' ;;
+    "create item")
+        n=$(( $(cat "$dir/next" 2>/dev/null || echo 0) + 1 ))
+        echo "$n" > "$dir/next"
+        printf '%s' "$3" | base64 -d \
+            | sed "s/^{/{\"id\":\"item-$n\",\"revisionDate\":\"2026-01-01T00:00:00.000Z\",/" \
+            > "$dir/items/item-$n.json"
+        cat "$dir/items/item-$n.json" ;;
+    "edit item")
+        printf '%s' "$4" | base64 -d > "$dir/items/$3.json"
+        cat "$dir/items/$3.json" ;;
+    "get item") cat "$dir/items/$3.json" 2>/dev/null || { echo "Not found." >&2; exit 1; } ;;
+    "delete item") rm "$dir/items/$3.json" ;;
+    "create attachment" | "delete attachment") ;;
+    *) echo "unsupported: $*" >&2; exit 1 ;;
+esac
+"#;
+
+// Tests share the process's PATH, so only one fake vault is active at a time
+static ACTIVE: Mutex<()> = Mutex::new(());
+
+/// A fake `bw` on the front of PATH, backed by a scratch directory, for the test's lifetime
+pub struct FakeBw {
+    dir: PathBuf,
+    original_path: Option<OsString>,
+    _active: MutexGuard<'static, ()>,
+}
+
+impl FakeBw {
+    pub fn new() -> Self {
+        let active = ACTIVE
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let dir = std::env::temp_dir().join(format!("bw-env-fake-bw-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("items")).unwrap();
+        let script = dir.join("bw");
+        fs::write(&script, SCRIPT).unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let original_path = std::env::var_os("PATH");
+        let mut paths = vec![dir.clone()];
+        paths.extend(original_path.iter().flat_map(std::env::split_paths));
+        std::env::set_var("PATH", std::env::join_paths(paths).unwrap());
+
+        FakeBw {
+            dir,
+            original_path,
+            _active: active,
+        }
+    }
+
+    /// Put an item straight into the fake vault
+    pub fn add_item(&self, item: &Value) {
+        let id = item["id"].as_str().unwrap();
+        fs::write(
+            self.dir.join("items").join(format!("{}.json", id)),
+            item.to_string(),
+        )
+        .unwrap();
+    }
+
+    /// Item currently stored under the id, if any
+    pub fn item(&self, id: &str) -> Option<Value> {
+        let raw = fs::read_to_string(self.dir.join("items").join(format!("{}.json", id))).ok()?;
+        serde_json::from_str(&raw).ok()
+    }
+
+    /// Ids of every item in the fake vault
+    pub fn item_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = fs::read_dir(self.dir.join("items"))
+            .unwrap()
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                name.strip_suffix(".json").map(str::to_string)
+            })
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    /// Make every later `bw <command> <object>` call fail
+    pub fn fail(&self, command: &str, object: &str) {
+        self.fail_after(command, object, 0);
+    }
+
+    /// Let `successes` more `bw <command> <object>` calls succeed, then fail the rest
+    pub fn fail_after(&self, command: &str, object: &str, successes: usize) {
+        let path = self.dir.join(format!("fail-{}-{}", command, object));
+        fs::write(path, successes.to_string()).unwrap();
+    }
+
+    /// `<command> <object>` of every call so far, in order
+    pub fn calls(&self) -> Vec<String> {
+        fs::read_to_string(self.dir.join("calls"))
+            .unwrap_or_default()
+            .lines()
+            .map(|line| line.trim_end().to_string())
+            .collect()
+    }
+}
+
+impl Drop for FakeBw {
+    fn drop(&mut self) {
+        match &self.original_path {
+            Some(path) => std::env::set_var("PATH", path),
+            None => std::env::remove_var("PATH"),
+        }
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
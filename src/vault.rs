@@ -0,0 +1,89 @@
+use crate::bw_commands::get_server_url;
+
+// Overrides the web vault URL, e.g. when the web vault is served from another host
+const VAULT_URL_ENV_VAR: &str = "BW_ENV_VAULT_URL";
+
+const US_VAULT_URL: &str = "https://vault.bitwarden.com";
+const EU_VAULT_URL: &str = "https://vault.bitwarden.eu";
+
+/// Base URL of the web vault for the configured server.
+///
+/// Uses `BW_ENV_VAULT_URL` when set, otherwise `bw config server`: the Bitwarden cloud
+/// regions map to their web vault hosts, a self-hosted server (e.g. Vaultwarden) serves
+/// the web vault itself.
+pub fn web_vault_url() -> String {
+    let configured = std::env::var(VAULT_URL_ENV_VAR)
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+        .or_else(|| get_server_url().ok())
+        .unwrap_or_default();
+    vault_url_for(&configured)
+}
+
+/// Web vault URL for a configured server URL
+fn vault_url_for(configured: &str) -> String {
+    let url = configured.trim().trim_end_matches('/');
+
+    let host = url
+        .split_once("://")
+        .map_or(url, |(_, rest)| rest)
+        .split('/')
+        .next()
+        .unwrap_or_default();
+    match host {
+        "" | "bitwarden.com" | "vault.bitwarden.com" => US_VAULT_URL.to_string(),
+        "bitwarden.eu" | "vault.bitwarden.eu" => EU_VAULT_URL.to_string(),
+        _ => url.to_string(),
+    }
+}
+
+/// Link to an item in the web vault at `base_url`
+pub fn item_link(base_url: &str, folder_id: &str, item_id: &str) -> String {
+    format!(
+        "{}/#/vault?folderId={}&itemId={}&action=view",
+        base_url, folder_id, item_id
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cloud_servers_map_to_their_web_vaults() {
+        for configured in [
+            "",
+            "https://bitwarden.com",
+            "https://vault.bitwarden.com/\n",
+        ] {
+            assert_eq!(vault_url_for(configured), US_VAULT_URL);
+        }
+        for configured in ["https://bitwarden.eu", "https://vault.bitwarden.eu/"] {
+            assert_eq!(vault_url_for(configured), EU_VAULT_URL);
+        }
+    }
+
+    #[test]
+    fn self_hosted_servers_serve_their_own_web_vault() {
+        assert_eq!(
+            vault_url_for("https://vault.example.com/"),
+            "https://vault.example.com"
+        );
+        assert_eq!(
+            vault_url_for("https://example.com/bitwarden"),
+            "https://example.com/bitwarden"
+        );
+        assert_eq!(
+            vault_url_for("http://localhost:8080"),
+            "http://localhost:8080"
+        );
+    }
+
+    #[test]
+    fn links_to_the_item_in_its_folder() {
+        assert_eq!(
+            item_link("https://vault.example.com", "folder-1", "item-1"),
+            "https://vault.example.com/#/vault?folderId=folder-1&itemId=item-1&action=view"
+        );
+    }
+}